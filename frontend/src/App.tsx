@@ -9,7 +9,7 @@ import TitleBar from "./components/TitleBar";
 export default function App() {
   const {
     path, limitMb, running, status, phase, progress, speedMbps,
-    bytesWritten, bytesVerified, totalBytes, report, toast, language,
+    bytesWritten, bytesVerified, totalBytes, stall, stallCount, report, toast, language,
     disks, isScanningDisks,
     setLimitMb, setLanguage,
     scanDisks, selectDisk, startDiagnosis, stopDiagnosis
@@ -43,7 +43,12 @@ export default function App() {
     Healthy: t("report.status.healthy"),
     FakeCapacity: t("report.status.fakeCapacity"),
     PhysicalCorruption: t("report.status.physicalCorruption"),
-    DataLoss: t("report.status.dataLoss")
+    DataLoss: t("report.status.dataLoss"),
+    StaleData: t("report.status.staleData"),
+    Degraded: t("report.status.degraded"),
+    ReadOnly: t("report.status.readOnly"),
+    Disconnected: t("report.status.disconnected"),
+    Unstable: t("report.status.unstable")
   };
 
   const currentView = running ? "progress" : report ? "report" : "setup";
@@ -120,6 +125,8 @@ export default function App() {
                       speedMbps={speedMbps}
                       etaSeconds={etaSeconds}
                       totalBytes={totalBytes}
+                      stall={stall}
+                      stallCount={stallCount}
                       handleStop={() => stopDiagnosis(language)}
                   />
               )}
@@ -1,4 +1,5 @@
 import { byteFormatter, timeFormatter, Translator } from "./diagnosisUtils";
+import { StallEvent } from "../store";
 
 type ProgressViewProps = {
   t: Translator;
@@ -11,6 +12,8 @@ type ProgressViewProps = {
   speedMbps: number;
   etaSeconds: number | null;
   totalBytes: number;
+  stall: StallEvent | null;
+  stallCount: number;
   handleStop: () => Promise<void>;
 };
 
@@ -25,6 +28,8 @@ const ProgressView = ({
                         speedMbps,
                         etaSeconds,
                         totalBytes,
+                        stall,
+                        stallCount,
                         handleStop
                       }: ProgressViewProps) => {
 
@@ -45,7 +50,13 @@ const ProgressView = ({
             </span>
           </div>
           <div className="text-xs font-mono text-slate-500">
-            {}
+            {stall ? (
+                <span className="text-amber-400 animate-pulse">
+                  {t("progress.stalled", { seconds: String(Math.round(stall.duration_ms / 1000)) })}
+                </span>
+            ) : stallCount > 0 ? (
+                <span className="text-amber-500">{t("progress.stalls", { count: String(stallCount) })}</span>
+            ) : null}
           </div>
         </div>
 
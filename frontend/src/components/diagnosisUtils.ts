@@ -9,7 +9,12 @@ export const statusStyles: Record<DiagnosisReport["status"], string> = {
   Healthy: "border-mint-500/40 bg-mint-500/15 text-mint-400",
   FakeCapacity: "border-ember-500/50 bg-ember-500/20 text-ember-400",
   PhysicalCorruption: "border-ember-500/50 bg-ember-500/20 text-ember-400",
-  DataLoss: "border-ember-500/50 bg-ember-500/20 text-ember-400"
+  DataLoss: "border-ember-500/50 bg-ember-500/20 text-ember-400",
+  StaleData: "border-ember-500/50 bg-ember-500/20 text-ember-400",
+  Degraded: "border-ember-500/50 bg-ember-500/20 text-ember-400",
+  ReadOnly: "border-ember-500/50 bg-ember-500/20 text-ember-400",
+  Disconnected: "border-ember-500/50 bg-ember-500/20 text-ember-400",
+  Unstable: "border-ember-500/50 bg-ember-500/20 text-ember-400"
 };
 
 export const byteFormatter = (
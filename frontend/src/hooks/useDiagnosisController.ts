@@ -2,7 +2,7 @@ import { useEffect, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { join } from "@tauri-apps/api/path";
-import { useDiagnosisStore, ProgressUpdate, DiagnosisReport, DiskInfo } from "../store";
+import { useDiagnosisStore, ProgressUpdate, DiagnosisReport, DiskInfo, StallEvent } from "../store";
 import { Locale } from "../i18n";
 
 export function useDiagnosisController() {
@@ -29,6 +29,11 @@ export function useDiagnosisController() {
                 store.setStatus("error");
             });
 
+            const unlistenStall = await listen<StallEvent>("STALL_DETECTED", (event) => {
+                if (!mounted) return;
+                store.recordStall(event.payload);
+            });
+
             const unlistenComplete = await listen<DiagnosisReport>("DIAGNOSIS_COMPLETE", (event) => {
                 if (!mounted) return;
                 store.setReport(event.payload);
@@ -43,7 +48,7 @@ export function useDiagnosisController() {
                 store.setStatus("cancelled");
             });
 
-            unlisteners.push(unlistenProgress, unlistenError, unlistenComplete, unlistenCancelled);
+            unlisteners.push(unlistenProgress, unlistenError, unlistenStall, unlistenComplete, unlistenCancelled);
         };
 
         setupListeners();
@@ -36,6 +36,8 @@ const de =
   "placeholder.capacityLimit": "Leer lassen für gesamten freien Speicher",
   "placeholder.targetFile": "Bitte wählen Sie ein Laufwerk oder einen Ordner",
   "progress.detail": "{written} geschrieben · {verified} verifiziert",
+  "progress.stalled": "Hängt seit {seconds} s",
+  "progress.stalls": "Hänger: {count}",
   "progress.title": "Aktueller Status",
   "progress.unlimited": "Unbegrenzter Modus",
   "report.alert.dataLoss": "KRITISCH: Hohes Risiko für Datenverlust erkannt",
@@ -51,6 +53,11 @@ const de =
   "report.returnRecommendation": "Empfehlung: Sofortige Reklamation/Rückgabe",
   "report.returnRecommendationEyebrow": "Expertenrat",
   "report.status.dataLoss": "Datenverlust",
+  "report.status.staleData": "Veraltete Daten",
+  "report.status.degraded": "Leistungsschwach",
+  "report.status.readOnly": "Schreibgeschützt",
+  "report.status.disconnected": "Getrennt",
+  "report.status.unstable": "Instabil",
   "report.status.fakeCapacity": "Fake-Kapazität",
   "report.status.healthy": "Fehlerfrei",
   "report.status.physicalCorruption": "Physisch defekt",
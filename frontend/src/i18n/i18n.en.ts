@@ -36,6 +36,8 @@ const en =
     "placeholder.capacityLimit": "Leave empty to test all available space",
     "placeholder.targetFile": "Select a drive or folder to begin testing",
     "progress.detail": "Written: {written} · Verified: {verified}",
+    "progress.stalled": "Stalled for {seconds} s",
+    "progress.stalls": "Stalls: {count}",
     "progress.title": "Current Task",
     "progress.unlimited": "Unlimited Mode",
     "report.alert.dataLoss": "CRITICAL: Data Loss Risk Detected",
@@ -51,6 +53,11 @@ const en =
     "report.returnRecommendation": "Recommendation: Return/Refund Immediately",
     "report.returnRecommendationEyebrow": "Expert Advice",
     "report.status.dataLoss": "Data Loss",
+    "report.status.staleData": "Stale Data",
+    "report.status.degraded": "Degraded",
+    "report.status.readOnly": "Read-Only",
+    "report.status.disconnected": "Disconnected",
+    "report.status.unstable": "Unstable",
     "report.status.fakeCapacity": "Fake Capacity",
     "report.status.healthy": "Healthy",
     "report.status.physicalCorruption": "Bad Sectors",
@@ -36,6 +36,8 @@ const es =
     "placeholder.capacityLimit": "Dejar vacío para probar todo el espacio disponible",
     "placeholder.targetFile": "Haga clic en 'Seleccionar ruta' para elegir una unidad o carpeta",
     "progress.detail": "Escritos: {written} · Verificados: {verified}",
+    "progress.stalled": "Detenido durante {seconds} s",
+    "progress.stalls": "Bloqueos: {count}",
     "progress.title": "Tarea actual",
     "progress.unlimited": "Modo sin límites",
     "report.alert.dataLoss": "ALERTA CRÍTICA: Riesgo de pérdida de datos detectado",
@@ -51,6 +53,11 @@ const es =
     "report.returnRecommendation": "Se recomienda solicitar la devolución/reembolso inmediatamente",
     "report.returnRecommendationEyebrow": "Consejo de experto",
     "report.status.dataLoss": "Pérdida de datos",
+    "report.status.staleData": "Datos obsoletos",
+    "report.status.degraded": "Degradado",
+    "report.status.readOnly": "Solo lectura",
+    "report.status.disconnected": "Desconectado",
+    "report.status.unstable": "Inestable",
     "report.status.fakeCapacity": "Capacidad inflada",
     "report.status.healthy": "Saludable",
     "report.status.physicalCorruption": "Daño físico",
@@ -36,6 +36,8 @@ const fr =
   "placeholder.capacityLimit": "Laisser vide pour tester tout l'espace disponible",
   "placeholder.targetFile": "Sélectionnez un lecteur ou un dossier à tester",
   "progress.detail": "Écrit : {written} · Vérifié : {verified}",
+  "progress.stalled": "Bloqué depuis {seconds} s",
+  "progress.stalls": "Blocages : {count}",
   "progress.title": "Tâche en cours",
   "progress.unlimited": "Mode illimité",
   "report.alert.dataLoss": "Alerte Critique : Risque de perte de données détecté",
@@ -51,6 +53,11 @@ const fr =
   "report.returnRecommendation": "Retour/Remboursement conseillé immédiatement",
   "report.returnRecommendationEyebrow": "Avis d'expert",
   "report.status.dataLoss": "Perte de données",
+  "report.status.staleData": "Données obsolètes",
+  "report.status.degraded": "Dégradé",
+  "report.status.readOnly": "Lecture seule",
+  "report.status.disconnected": "Déconnecté",
+  "report.status.unstable": "Instable",
   "report.status.fakeCapacity": "Capacité falsifiée",
   "report.status.healthy": "Sain",
   "report.status.physicalCorruption": "Dommages physiques",
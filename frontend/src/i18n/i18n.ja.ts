@@ -36,6 +36,8 @@ const ja =
   "placeholder.capacityLimit": "空欄の場合はすべての空き領域を診断します",
   "placeholder.targetFile": "「参照」ボタンからドライブまたはフォルダを選択",
   "progress.detail": "書き込み: {written} · 検証済み: {verified}",
+  "progress.stalled": "{seconds} 秒間停止中",
+  "progress.stalls": "停止回数: {count}",
   "progress.title": "現在のタスク",
   "progress.unlimited": "無制限モード",
   "report.alert.dataLoss": "【重要】データ消失のリスクが検出されました",
@@ -51,6 +53,11 @@ const ja =
   "report.returnRecommendation": "直ちに返品・返金手続きを行うことを推奨します",
   "report.returnRecommendationEyebrow": "専門家からのアドバイス",
   "report.status.dataLoss": "データ消失",
+  "report.status.staleData": "古いデータ",
+  "report.status.degraded": "性能低下",
+  "report.status.readOnly": "読み取り専用",
+  "report.status.disconnected": "切断",
+  "report.status.unstable": "読み取り不安定",
   "report.status.fakeCapacity": "容量偽装",
   "report.status.healthy": "正常",
   "report.status.physicalCorruption": "物理破損",
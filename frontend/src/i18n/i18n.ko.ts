@@ -36,6 +36,8 @@ const ko =
   "placeholder.capacityLimit": "비워두면 사용 가능한 모든 공간을 검사합니다",
   "placeholder.targetFile": "'찾아보기'를 클릭하여 드라이브나 폴더를 선택하세요",
   "progress.detail": "기록됨 {written} · 검증됨 {verified}",
+  "progress.stalled": "{seconds}초 동안 멈춤",
+  "progress.stalls": "멈춤 횟수: {count}",
   "progress.title": "작업 현황",
   "progress.unlimited": "무제한 모드",
   "report.alert.dataLoss": "심각한 경고: 데이터 손실 위험 감지됨",
@@ -51,6 +53,11 @@ const ko =
   "report.returnRecommendation": "즉시 반품/환불 권장",
   "report.returnRecommendationEyebrow": "전문가 제안",
   "report.status.dataLoss": "데이터 손실",
+  "report.status.staleData": "오래된 데이터",
+  "report.status.degraded": "성능 저하",
+  "report.status.readOnly": "읽기 전용",
+  "report.status.disconnected": "연결 끊김",
+  "report.status.unstable": "불안정",
   "report.status.fakeCapacity": "용량 조작",
   "report.status.healthy": "정상",
   "report.status.physicalCorruption": "물리적 손상",
@@ -36,6 +36,8 @@ const ru =
   "placeholder.capacityLimit": "Оставьте пустым для проверки всего свободного места",
   "placeholder.targetFile": "Нажмите «Обзор...», чтобы выбрать диск или папку",
   "progress.detail": "Записано: {written} · Проверено: {verified}",
+  "progress.stalled": "Зависание {seconds} с",
+  "progress.stalls": "Зависаний: {count}",
   "progress.title": "Текущая задача",
   "progress.unlimited": "Безлимитный режим",
   "report.alert.dataLoss": "КРИТИЧЕСКИЙ СБОЙ: Высокий риск потери данных",
@@ -51,6 +53,11 @@ const ru =
   "report.returnRecommendation": "Рекомендуется немедленный возврат/обмен товара",
   "report.returnRecommendationEyebrow": "Совет эксперта",
   "report.status.dataLoss": "Потеря данных",
+  "report.status.staleData": "Устаревшие данные",
+  "report.status.degraded": "Деградация",
+  "report.status.readOnly": "Только чтение",
+  "report.status.disconnected": "Отключен",
+  "report.status.unstable": "Нестабилен",
   "report.status.fakeCapacity": "Поддельный объем",
   "report.status.healthy": "Здоров",
   "report.status.physicalCorruption": "Физич. повреждения",
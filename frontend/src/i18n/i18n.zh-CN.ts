@@ -36,6 +36,8 @@ const zhCn =
   "placeholder.capacityLimit": "留空则默认检测所有可用空间",
   "placeholder.targetFile": "请点击浏览选择要测试的驱动器或文件夹",
   "progress.detail": "已写入 {written} · 已验证 {verified}",
+  "progress.stalled": "已停顿 {seconds} 秒",
+  "progress.stalls": "停顿次数：{count}",
   "progress.title": "当前任务",
   "progress.unlimited": "无限制模式",
   "report.alert.dataLoss": "严重警告：检测到数据丢失风险",
@@ -51,6 +53,11 @@ const zhCn =
   "report.returnRecommendation": "建议立即申请退货/退款",
   "report.returnRecommendationEyebrow": "专家建议",
   "report.status.dataLoss": "数据丢失",
+  "report.status.staleData": "陈旧数据",
+  "report.status.degraded": "性能降级",
+  "report.status.readOnly": "只读",
+  "report.status.disconnected": "已断开",
+  "report.status.unstable": "读取不稳定",
   "report.status.fakeCapacity": "虚标扩容",
   "report.status.healthy": "健康",
   "report.status.physicalCorruption": "物理损坏",
@@ -36,6 +36,8 @@ const zhTw =
   "placeholder.capacityLimit": "留空則預設檢測所有可用空間",
   "placeholder.targetFile": "請點擊瀏覽選擇要測試的磁碟機或資料夾",
   "progress.detail": "已寫入 {written} · 已驗證 {verified}",
+  "progress.stalled": "已停頓 {seconds} 秒",
+  "progress.stalls": "停頓次數：{count}",
   "progress.title": "目前任務",
   "progress.unlimited": "無限制模式",
   "report.alert.dataLoss": "嚴重警告：檢測到資料遺失風險",
@@ -51,6 +53,11 @@ const zhTw =
   "report.returnRecommendation": "建議立即申請退貨/退款",
   "report.returnRecommendationEyebrow": "專家建議",
   "report.status.dataLoss": "資料遺失",
+  "report.status.staleData": "陳舊資料",
+  "report.status.degraded": "效能降級",
+  "report.status.readOnly": "唯讀",
+  "report.status.disconnected": "已中斷連線",
+  "report.status.unstable": "讀取不穩定",
   "report.status.fakeCapacity": "虛標擴容",
   "report.status.healthy": "健康",
   "report.status.physicalCorruption": "物理損壞",
@@ -12,14 +12,142 @@ export interface ProgressUpdate {
   total_bytes: number;
 }
 
+// Sent while a request is still hanging, with no offset, and again with
+// `finished` once it returns.
+export interface StallEvent {
+  phase: ProgressPhase;
+  offset: number | null;
+  duration_ms: number;
+  finished: boolean;
+}
+
 export interface DiagnosisReport {
   total_capacity: number;
   tested_bytes: number;
   valid_bytes: number;
+  corrupted_bytes: number;
   error_count: number;
+  write_error_blocks: number;
   health_score: number;
-  status: "Healthy" | "FakeCapacity" | "PhysicalCorruption" | "DataLoss";
+  status:
+    | "Healthy"
+    | "FakeCapacity"
+    | "PhysicalCorruption"
+    | "DataLoss"
+    | "StaleData"
+    | "Degraded"
+    | "ReadOnly"
+    | "Disconnected"
+    | "Unstable";
   conclusion: string;
+  session_seed: string;
+  pattern_generator: string;
+  misplaced_sectors: MisplacedSector[];
+  usable_capacity: number | null;
+  wrap_modulus: number | null;
+  bit_errors: BitErrorStats;
+  failures: FailureBreakdown;
+  unstable_blocks: number;
+  recovered_reads: number;
+  write_speed_mbps: number | null;
+  verify_speed_mbps: number | null;
+  write_latency: LatencyStats;
+  read_latency: LatencyStats;
+  write_throughput: ThroughputCurve;
+  read_throughput: ThroughputCurve;
+  write_cache: WriteCacheEstimate | null;
+  write_cache_skip: "non_sequential_write" | "resumed_write" | null;
+  bad_ranges: BadRange[];
+  geometry: DriveGeometry | null;
+  buffered_io: boolean;
+  passes: PassReport[];
+}
+
+// One write and verify pass of a multi-pass run; empty for a single pass.
+export interface PassReport {
+  pattern_generator: string;
+  status: DiagnosisReport["status"];
+  tested_bytes: number;
+  error_count: number;
+  corrupted_bytes: number;
+  write_speed_mbps: number | null;
+  verify_speed_mbps: number | null;
+}
+
+// `offset` is where each region ends, in bytes completed so far.
+export interface ThroughputPoint {
+  offset: number;
+  elapsed_secs: number;
+  speed_mbps: number;
+}
+
+export interface ThroughputCurve {
+  region_bytes: number;
+  points: ThroughputPoint[];
+}
+
+export interface WriteCacheEstimate {
+  cache_bytes: number;
+  cached_mbps: number;
+  sustained_mbps: number;
+}
+
+export interface SlowIo {
+  offset: number;
+  latency_us: number;
+}
+
+export interface LatencyStats {
+  count: number;
+  total_us: number;
+  max_us: number;
+  histogram: number[];
+  slowest: SlowIo[];
+  stall_threshold_us: number;
+  stalls: number;
+}
+
+export interface DriveGeometry {
+  logical_sector_size: number | null;
+  physical_sector_size: number | null;
+  dio_mem_align: number | null;
+  dio_offset_align: number | null;
+  fs_block_size: number | null;
+  alignment: number;
+}
+
+export interface BadRange {
+  start: number;
+  end: number;
+  kind: "mismatch" | "read_error" | "write_error";
+}
+
+export interface FailureBreakdown {
+  zero_fill: number;
+  ones_fill: number;
+  repeated_block: number;
+  misplaced_data: number;
+  stale_session: number;
+  foreign_data: number;
+  bit_corruption: number;
+}
+
+export interface BitErrorStats {
+  flipped_bits: number;
+  zero_to_one: number;
+  one_to_zero: number;
+  zero_to_one_ratio: number | null;
+  positions: { zero_to_one: number; one_to_zero: number }[];
+  stuck_bits: { position: number; stuck_at: number }[];
+  sectors_with_errors: number;
+  max_bits_per_sector: number;
+  sector_histogram: number[];
+  bit_error_rate: number;
+}
+
+export interface MisplacedSector {
+  read_offset: number;
+  written_offset: number;
 }
 
 export interface DiskInfo {
@@ -41,6 +169,8 @@ interface DiagnosisState {
   bytesWritten: number;
   bytesVerified: number;
   totalBytes: number;
+  stall: StallEvent | null;
+  stallCount: number;
   report: DiagnosisReport | null;
   toast: string | null;
   language: Locale;
@@ -51,6 +181,7 @@ interface DiagnosisState {
   setRunning: (running: boolean) => void;
   setStatus: (status: DiagnosisState["status"]) => void;
   updateProgress: (update: ProgressUpdate) => void;
+  recordStall: (event: StallEvent) => void;
   setReport: (report: DiagnosisReport | null) => void;
   setToast: (message: string | null) => void;
   setLanguage: (language: Locale) => void;
@@ -70,6 +201,8 @@ export const useDiagnosisStore = create<DiagnosisState>((set) => ({
   bytesWritten: 0,
   bytesVerified: 0,
   totalBytes: 0,
+  stall: null,
+  stallCount: 0,
   report: null,
   toast: null,
   language: getInitialLocale(),
@@ -88,6 +221,11 @@ export const useDiagnosisStore = create<DiagnosisState>((set) => ({
         bytesVerified: update.bytes_verified,
         totalBytes: update.total_bytes
       }),
+  recordStall: (event) =>
+      set((state) => ({
+        stall: event.finished ? null : event,
+        stallCount: event.finished ? state.stallCount + 1 : state.stallCount
+      })),
   setReport: (report) => set({ report }),
   setToast: (toast) => set({ toast }),
   setLanguage: (language) => {
@@ -105,6 +243,8 @@ export const useDiagnosisStore = create<DiagnosisState>((set) => ({
         bytesWritten: 0,
         bytesVerified: 0,
         totalBytes: 0,
+        stall: null,
+        stallCount: 0,
         report: null
       })
 }));
@@ -1,4 +1,4 @@
-﻿use truthbyte::{DiagnosisReport, DriveHealthStatus};
+﻿use truthbyte::{CacheEstimateSkip, DiagnosisReport, DriveHealthStatus};
 
 #[derive(Clone, Copy)]
 pub enum Locale {
@@ -314,15 +314,15 @@ pub fn localize_report_conclusion(locale: Locale, report: &DiagnosisReport) -> S
             Locale::Ja => "不整合は検出されませんでした。",
         },
         DriveHealthStatus::FakeCapacity => match locale {
-            Locale::En => "Warning: detected zero-filled data; possible fake capacity.",
-            Locale::Es => "Advertencia: se detectaron datos llenos de ceros; posible capacidad falsa.",
-            Locale::Fr => "Avertissement : des données remplies de zéros ont été détectées ; capacité potentiellement factice.",
-            Locale::De => "Warnung: mit Nullen gefüllte Daten erkannt; mögliche Falschkapazität.",
-            Locale::Ru => "Предупреждение: обнаружены заполненные нулями данные; возможна ложная емкость.",
-            Locale::Ko => "경고: 0으로 채워진 데이터가 감지되었습니다. 가짜 용량일 수 있습니다.",
-            Locale::ZhCn => "警告：检测到全零数据，可能为虚假容量。",
-            Locale::ZhTw => "警告：檢測到全零資料，可能為虛假容量。",
-            Locale::Ja => "警告：ゼロ埋めデータを検出しました。偽装容量の可能性があります。",
+            Locale::En => "Warning: drive returned fill data or data from other offsets; possible fake capacity.",
+            Locale::Es => "Advertencia: la unidad devolvió datos de relleno o de otros desplazamientos; posible capacidad falsa.",
+            Locale::Fr => "Avertissement : le support a renvoyé des données de remplissage ou d'autres positions ; capacité potentiellement factice.",
+            Locale::De => "Warnung: Laufwerk lieferte Fülldaten oder Daten anderer Offsets; mögliche Falschkapazität.",
+            Locale::Ru => "Предупреждение: накопитель вернул данные-заполнитель или данные с других смещений; возможна ложная емкость.",
+            Locale::Ko => "경고: 드라이브가 채움 데이터 또는 다른 오프셋의 데이터를 반환했습니다. 가짜 용량일 수 있습니다.",
+            Locale::ZhCn => "警告：设备返回了填充数据或其他偏移的数据，可能为虚假容量。",
+            Locale::ZhTw => "警告：裝置傳回了填充資料或其他位移的資料，可能為虛假容量。",
+            Locale::Ja => "警告：埋め草データまたは別オフセットのデータが返されました。偽装容量の可能性があります。",
         },
         DriveHealthStatus::PhysicalCorruption => match locale {
             Locale::En => "Warning: detected random corruption or inconsistent data.",
@@ -346,6 +346,61 @@ pub fn localize_report_conclusion(locale: Locale, report: &DiagnosisReport) -> S
             Locale::ZhTw => "警告：檢測到讀取錯誤或資料缺失。",
             Locale::Ja => "警告：読み取りエラーまたはデータ欠落を検出しました。",
         },
+        DriveHealthStatus::StaleData => match locale {
+            Locale::En => "Warning: drive returned data that was never written by this test.",
+            Locale::Es => "Advertencia: la unidad devolvió datos que esta prueba nunca escribió.",
+            Locale::Fr => "Avertissement : le support a renvoyé des données jamais écrites par ce test.",
+            Locale::De => "Warnung: Laufwerk lieferte Daten, die dieser Test nie geschrieben hat.",
+            Locale::Ru => "Предупреждение: накопитель вернул данные, которые этот тест не записывал.",
+            Locale::Ko => "경고: 드라이브가 이 테스트에서 기록하지 않은 데이터를 반환했습니다.",
+            Locale::ZhCn => "警告：设备返回了本次测试从未写入的数据。",
+            Locale::ZhTw => "警告：裝置傳回了本次測試從未寫入的資料。",
+            Locale::Ja => "警告：このテストで書き込んでいないデータが返されました。",
+        },
+        DriveHealthStatus::Degraded => match locale {
+            Locale::En => "Data verified, but the drive is far too slow for normal use.",
+            Locale::Es => "Datos verificados, pero la unidad es demasiado lenta para un uso normal.",
+            Locale::Fr => "Données vérifiées, mais le support est bien trop lent pour un usage normal.",
+            Locale::De => "Daten verifiziert, aber das Laufwerk ist für den normalen Gebrauch viel zu langsam.",
+            Locale::Ru => "Данные проверены, но накопитель слишком медленный для обычной работы.",
+            Locale::Ko => "데이터는 검증되었지만 드라이브가 정상적으로 사용하기에는 너무 느립니다.",
+            Locale::ZhCn => "数据校验通过，但设备速度过慢，无法正常使用。",
+            Locale::ZhTw => "資料驗證通過，但裝置速度過慢，無法正常使用。",
+            Locale::Ja => "データは検証されましたが、通常の使用には速度が遅すぎます。",
+        },
+        DriveHealthStatus::ReadOnly => match locale {
+            Locale::En => "Warning: drive is write-protected or mounted read-only.",
+            Locale::Es => "Advertencia: la unidad está protegida contra escritura o montada como solo lectura.",
+            Locale::Fr => "Avertissement : le support est protégé en écriture ou monté en lecture seule.",
+            Locale::De => "Warnung: Laufwerk ist schreibgeschützt oder schreibgeschützt eingehängt.",
+            Locale::Ru => "Предупреждение: накопитель защищен от записи или смонтирован только для чтения.",
+            Locale::Ko => "경고: 드라이브가 쓰기 보호되어 있거나 읽기 전용으로 마운트되었습니다.",
+            Locale::ZhCn => "警告：设备处于写保护状态或以只读方式挂载。",
+            Locale::ZhTw => "警告：裝置處於防寫狀態或以唯讀方式掛載。",
+            Locale::Ja => "警告：ドライブが書き込み禁止、または読み取り専用でマウントされています。",
+        },
+        DriveHealthStatus::Disconnected => match locale {
+            Locale::En => "Warning: drive disconnected during the test.",
+            Locale::Es => "Advertencia: la unidad se desconectó durante la prueba.",
+            Locale::Fr => "Avertissement : le support a été déconnecté pendant le test.",
+            Locale::De => "Warnung: Laufwerk wurde während des Tests getrennt.",
+            Locale::Ru => "Предупреждение: накопитель отключился во время проверки.",
+            Locale::Ko => "경고: 테스트 중 드라이브 연결이 끊어졌습니다.",
+            Locale::ZhCn => "警告：测试过程中设备已断开连接。",
+            Locale::ZhTw => "警告：測試過程中裝置已中斷連線。",
+            Locale::Ja => "警告：テスト中にドライブが切断されました。",
+        },
+        DriveHealthStatus::Unstable => match locale {
+            Locale::En => "Warning: drive returned different data on re-read.",
+            Locale::Es => "Advertencia: la unidad devolvió datos distintos al volver a leer.",
+            Locale::Fr => "Avertissement : le support a renvoyé des données différentes à la relecture.",
+            Locale::De => "Warnung: Laufwerk lieferte beim erneuten Lesen andere Daten.",
+            Locale::Ru => "Предупреждение: при повторном чтении накопитель вернул другие данные.",
+            Locale::Ko => "경고: 다시 읽을 때 드라이브가 다른 데이터를 반환했습니다.",
+            Locale::ZhCn => "警告：重复读取时设备返回了不同的数据。",
+            Locale::ZhTw => "警告：重複讀取時裝置傳回了不同的資料。",
+            Locale::Ja => "警告：再読み込みで異なるデータが返されました。",
+        },
     };
 
     let mut conclusion = match locale {
@@ -401,5 +456,223 @@ pub fn localize_report_conclusion(locale: Locale, report: &DiagnosisReport) -> S
         }
     }
 
+    if let Some(sector) = report.misplaced_sectors.first() {
+        let (read, written) = (sector.read_offset, sector.written_offset);
+        conclusion.push_str(&match locale {
+            Locale::En => format!(" Offset {read} returned the data written at offset {written}."),
+            Locale::Es => format!(
+                " El desplazamiento {read} devolvió los datos escritos en el desplazamiento {written}."
+            ),
+            Locale::Fr => format!(" L'octet {read} a renvoyé les données écrites à l'octet {written}."),
+            Locale::De => format!(
+                " Offset {read} lieferte die bei Offset {written} geschriebenen Daten."
+            ),
+            Locale::Ru => format!(
+                " Смещение {read} вернуло данные, записанные по смещению {written}."
+            ),
+            Locale::Ko => format!(" 오프셋 {read}에서 오프셋 {written}에 기록한 데이터가 반환되었습니다."),
+            Locale::ZhCn => format!(" 偏移 {read} 读回了写入偏移 {written} 的数据。"),
+            Locale::ZhTw => format!(" 位移 {read} 讀回了寫入位移 {written} 的資料。"),
+            Locale::Ja => format!(
+                " オフセット {read} からオフセット {written} に書き込んだデータが返されました。"
+            ),
+        });
+    }
+
+    if report.status == DriveHealthStatus::FakeCapacity
+        && let Some(usable) = report.usable_capacity
+    {
+        conclusion.push_str(&match locale {
+            Locale::En => format!(" Usable capacity: {usable} bytes."),
+            Locale::Es => format!(" Capacidad utilizable: {usable} bytes."),
+            Locale::Fr => format!(" Capacité utilisable : {usable} octets."),
+            Locale::De => format!(" Nutzbare Kapazität: {usable} Bytes."),
+            Locale::Ru => format!(" Доступная емкость: {usable} байт."),
+            Locale::Ko => format!(" 실제 사용 가능 용량: {usable} 바이트."),
+            Locale::ZhCn => format!(" 实际可用容量：{usable} 字节。"),
+            Locale::ZhTw => format!(" 實際可用容量：{usable} 位元組。"),
+            Locale::Ja => format!(" 実際の使用可能容量：{usable} バイト。"),
+        });
+    }
+
+    if let Some(modulus) = report.wrap_modulus {
+        conclusion.push_str(&match locale {
+            Locale::En => format!(" Address space wraps every {modulus} bytes."),
+            Locale::Es => format!(" El espacio de direcciones se repite cada {modulus} bytes."),
+            Locale::Fr => format!(" L'espace d'adressage boucle tous les {modulus} octets."),
+            Locale::De => format!(" Der Adressraum wiederholt sich alle {modulus} Bytes."),
+            Locale::Ru => format!(" Адресное пространство повторяется каждые {modulus} байт."),
+            Locale::Ko => format!(" 주소 공간이 {modulus} 바이트마다 반복됩니다."),
+            Locale::ZhCn => format!(" 地址空间每 {modulus} 字节循环一次。"),
+            Locale::ZhTw => format!(" 位址空間每 {modulus} 位元組循環一次。"),
+            Locale::Ja => format!(" アドレス空間は {modulus} バイトごとに折り返します。"),
+        });
+    }
+
+    let unwritten = report.write_error_blocks;
+    if unwritten > 0 {
+        conclusion.push_str(&match locale {
+            Locale::En => format!(" {unwritten} blocks could not be written."),
+            Locale::Es => format!(" No se pudieron escribir {unwritten} bloques."),
+            Locale::Fr => format!(" {unwritten} blocs n'ont pas pu être écrits."),
+            Locale::De => format!(" {unwritten} Blöcke konnten nicht geschrieben werden."),
+            Locale::Ru => format!(" Не удалось записать {unwritten} блоков."),
+            Locale::Ko => format!(" {unwritten}개 블록을 기록하지 못했습니다."),
+            Locale::ZhCn => format!(" {unwritten} 个块无法写入。"),
+            Locale::ZhTw => format!(" {unwritten} 個區塊無法寫入。"),
+            Locale::Ja => format!(" {unwritten} 個のブロックに書き込めませんでした。"),
+        });
+    }
+
+    let recovered = report.recovered_reads;
+    if recovered > 0 {
+        conclusion.push_str(&match locale {
+            Locale::En => format!(" {recovered} blocks could only be read after a retry."),
+            Locale::Es => format!(" {recovered} bloques solo se pudieron leer tras un reintento."),
+            Locale::Fr => format!(" {recovered} blocs n'ont pu être lus qu'après une nouvelle tentative."),
+            Locale::De => format!(" {recovered} Blöcke konnten erst nach einem erneuten Versuch gelesen werden."),
+            Locale::Ru => format!(" {recovered} блоков удалось прочитать только после повторной попытки."),
+            Locale::Ko => format!(" {recovered}개 블록은 재시도 후에야 읽을 수 있었습니다."),
+            Locale::ZhCn => format!(" {recovered} 个块需要重试才能读取。"),
+            Locale::ZhTw => format!(" {recovered} 個區塊需要重試才能讀取。"),
+            Locale::Ja => format!(" {recovered} 個のブロックは再試行後にのみ読み取れました。"),
+        });
+    }
+
+    let stalls = report.write_latency.stalls + report.read_latency.stalls;
+    if stalls > 0 {
+        let seconds = report
+            .write_latency
+            .stall_threshold_us
+            .max(report.read_latency.stall_threshold_us) as f64
+            / 1_000_000.0;
+        conclusion.push_str(&match locale {
+            Locale::En => format!(" {stalls} I/O requests stalled for over {seconds} s."),
+            Locale::Es => format!(" {stalls} solicitudes de E/S se detuvieron durante más de {seconds} s."),
+            Locale::Fr => format!(" {stalls} requêtes d'E/S ont été bloquées plus de {seconds} s."),
+            Locale::De => format!(" {stalls} E/A-Anfragen hingen länger als {seconds} s."),
+            Locale::Ru => format!(" {stalls} запросов ввода-вывода зависали дольше {seconds} с."),
+            Locale::Ko => format!(" {stalls}개의 I/O 요청이 {seconds}초 넘게 멈췄습니다."),
+            Locale::ZhCn => format!(" {stalls} 次 I/O 请求停顿超过 {seconds} 秒。"),
+            Locale::ZhTw => format!(" {stalls} 次 I/O 請求停頓超過 {seconds} 秒。"),
+            Locale::Ja => format!(" {stalls} 件の I/O 要求が {seconds} 秒以上停止しました。"),
+        });
+    }
+
+    if let Some(cache) = &report.write_cache {
+        let (cached, sustained, bytes) =
+            (cache.cached_mbps, cache.sustained_mbps, cache.cache_bytes);
+        conclusion.push_str(&match locale {
+            Locale::En => format!(
+                " Write speed dropped from {cached:.0} to {sustained:.0} MB/s after {bytes} bytes."
+            ),
+            Locale::Es => format!(
+                " La velocidad de escritura bajó de {cached:.0} a {sustained:.0} MB/s tras {bytes} bytes."
+            ),
+            Locale::Fr => format!(
+                " La vitesse d'écriture est passée de {cached:.0} à {sustained:.0} Mo/s après {bytes} octets."
+            ),
+            Locale::De => format!(
+                " Die Schreibgeschwindigkeit fiel nach {bytes} Bytes von {cached:.0} auf {sustained:.0} MB/s."
+            ),
+            Locale::Ru => format!(
+                " Скорость записи упала с {cached:.0} до {sustained:.0} МБ/с после {bytes} байт."
+            ),
+            Locale::Ko => format!(
+                " {bytes} 바이트 이후 쓰기 속도가 {cached:.0} MB/s에서 {sustained:.0} MB/s로 떨어졌습니다."
+            ),
+            Locale::ZhCn => format!(
+                " 写入 {bytes} 字节后，写入速度从 {cached:.0} MB/s 降至 {sustained:.0} MB/s。"
+            ),
+            Locale::ZhTw => format!(
+                " 寫入 {bytes} 位元組後，寫入速度從 {cached:.0} MB/s 降至 {sustained:.0} MB/s。"
+            ),
+            Locale::Ja => format!(
+                " {bytes} バイト書き込んだ後、書き込み速度が {cached:.0} MB/s から {sustained:.0} MB/s に低下しました。"
+            ),
+        });
+    }
+
+    if let Some(skip) = report.write_cache_skip {
+        conclusion.push_str(match (skip, locale) {
+            (CacheEstimateSkip::NonSequentialWrite, Locale::En) => {
+                " The write cache was not estimated since the data was not written sequentially."
+            }
+            (CacheEstimateSkip::NonSequentialWrite, Locale::Es) => {
+                " No se estimó la caché de escritura porque los datos no se escribieron secuencialmente."
+            }
+            (CacheEstimateSkip::NonSequentialWrite, Locale::Fr) => {
+                " Le cache d'écriture n'a pas été estimé car les données n'ont pas été écrites séquentiellement."
+            }
+            (CacheEstimateSkip::NonSequentialWrite, Locale::De) => {
+                " Der Schreibcache wurde nicht geschätzt, da die Daten nicht sequenziell geschrieben wurden."
+            }
+            (CacheEstimateSkip::NonSequentialWrite, Locale::Ru) => {
+                " Кэш записи не оценивался, так как данные записывались не последовательно."
+            }
+            (CacheEstimateSkip::NonSequentialWrite, Locale::Ko) => {
+                " 데이터를 순차적으로 쓰지 않아 쓰기 캐시를 추정하지 않았습니다."
+            }
+            (CacheEstimateSkip::NonSequentialWrite, Locale::ZhCn) => {
+                " 数据未按顺序写入，因此未估算写入缓存。"
+            }
+            (CacheEstimateSkip::NonSequentialWrite, Locale::ZhTw) => {
+                " 資料未依序寫入，因此未估算寫入快取。"
+            }
+            (CacheEstimateSkip::NonSequentialWrite, Locale::Ja) => {
+                " データが順番に書き込まれなかったため、書き込みキャッシュは推定していません。"
+            }
+            (CacheEstimateSkip::ResumedWrite, Locale::En) => {
+                " The write cache was not estimated since the write was resumed."
+            }
+            (CacheEstimateSkip::ResumedWrite, Locale::Es) => {
+                " No se estimó la caché de escritura porque la escritura se reanudó."
+            }
+            (CacheEstimateSkip::ResumedWrite, Locale::Fr) => {
+                " Le cache d'écriture n'a pas été estimé car l'écriture a été reprise."
+            }
+            (CacheEstimateSkip::ResumedWrite, Locale::De) => {
+                " Der Schreibcache wurde nicht geschätzt, da das Schreiben fortgesetzt wurde."
+            }
+            (CacheEstimateSkip::ResumedWrite, Locale::Ru) => {
+                " Кэш записи не оценивался, так как запись была возобновлена."
+            }
+            (CacheEstimateSkip::ResumedWrite, Locale::Ko) => {
+                " 쓰기를 이어서 진행했기 때문에 쓰기 캐시를 추정하지 않았습니다."
+            }
+            (CacheEstimateSkip::ResumedWrite, Locale::ZhCn) => " 写入是续传的，因此未估算写入缓存。",
+            (CacheEstimateSkip::ResumedWrite, Locale::ZhTw) => " 寫入是續傳的，因此未估算寫入快取。",
+            (CacheEstimateSkip::ResumedWrite, Locale::Ja) => {
+                " 書き込みを再開したため、書き込みキャッシュは推定していません。"
+            }
+        });
+    }
+
+    if report.buffered_io {
+        conclusion.push_str(match locale {
+            Locale::En => {
+                " Direct I/O was unavailable; data may have been read from the page cache."
+            }
+            Locale::Es => {
+                " La E/S directa no estaba disponible; los datos pueden haberse leído de la caché de páginas."
+            }
+            Locale::Fr => {
+                " Les E/S directes étaient indisponibles ; les données ont pu être lues depuis le cache de pages."
+            }
+            Locale::De => {
+                " Direkte E/A war nicht verfügbar; Daten wurden möglicherweise aus dem Seitencache gelesen."
+            }
+            Locale::Ru => {
+                " Прямой ввод-вывод недоступен; данные могли быть прочитаны из страничного кэша."
+            }
+            Locale::Ko => " 직접 I/O를 사용할 수 없어 페이지 캐시에서 데이터를 읽었을 수 있습니다.",
+            Locale::ZhCn => " 直接 I/O 不可用，读回的数据可能来自页缓存。",
+            Locale::ZhTw => " 直接 I/O 無法使用，讀回的資料可能來自頁面快取。",
+            Locale::Ja => {
+                " ダイレクト I/O が使用できないため、ページキャッシュから読み込まれた可能性があります。"
+            }
+        });
+    }
+
     conclusion
 }
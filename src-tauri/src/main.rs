@@ -5,7 +5,10 @@ use std::sync::Arc;
 
 use sysinfo::Disks;
 use tauri::{AppHandle, Emitter, State};
-use truthbyte::{AppConfig, DiagnosisReport, DriveInspector, EventSink, ProgressUpdate};
+use truthbyte::{
+    device_error_status, AppConfig, DiagnosisReport, DriveInspector, EventSink, ProgressUpdate,
+    StallEvent,
+};
 
 mod i18n;
 use crate::i18n::{localize_conclusion, localize_error, localize_report_conclusion, Locale};
@@ -14,6 +17,7 @@ const EVENT_PROGRESS: &str = "PROGRESS_UPDATE";
 const EVENT_ERROR: &str = "ERROR_OCCURRED";
 const EVENT_COMPLETE: &str = "DIAGNOSIS_COMPLETE";
 const EVENT_CANCELLED: &str = "DIAGNOSIS_CANCELLED";
+const EVENT_STALL: &str = "STALL_DETECTED";
 
 #[derive(Default)]
 struct AppState {
@@ -49,6 +53,10 @@ impl EventSink for TauriEventSink {
         let localized = localize_error(self.locale, &message);
         let _ = self.app.emit(EVENT_ERROR, ErrorPayload { message: localized });
     }
+
+    fn stall(&self, event: StallEvent) {
+        let _ = self.app.emit(EVENT_STALL, event);
+    }
 }
 
 #[tauri::command]
@@ -97,23 +105,15 @@ async fn start_diagnosis(
             inspector.run_write_phase_with_events(limit_mb, Some(cancel_flag.clone()), Some(&sink));
 
         let mut report = match result {
-            Ok(bytes_written) => {
-                if bytes_written == 0 {
-                    DiagnosisReport {
-                        total_capacity: 0,
-                        tested_bytes: 0,
-                        valid_bytes: 0,
-                        error_count: 0,
-                        health_score: 0.0,
-                        status: truthbyte::DriveHealthStatus::DataLoss,
-                        conclusion: localize_conclusion(
-                            locale,
-                            "No data written; verification skipped.",
-                        ),
-                    }
+            Ok(written) => {
+                if written.bytes_written == 0 {
+                    DiagnosisReport::no_data(
+                        inspector.pattern(),
+                        localize_conclusion(locale, "No data written; verification skipped."),
+                    )
                 } else {
                     match inspector.run_verify_phase_with_events(
-                        bytes_written,
+                        &written,
                         Some(cancel_flag.clone()),
                         Some(&sink),
                     ) {
@@ -124,12 +124,19 @@ async fn start_diagnosis(
                             cancel_flag.store(false, Ordering::SeqCst);
                             return;
                         }
-                        Err(e) => {
-                            sink.error(format!("Verify phase failed: {}", e));
-                            running.store(false, Ordering::SeqCst);
-                            cancel_flag.store(false, Ordering::SeqCst);
-                            return;
-                        }
+                        Err(e) => match device_error_status(&e) {
+                            Some(status) => DiagnosisReport::interrupted(
+                                inspector.pattern(),
+                                limit_mb * 1024 * 1024,
+                                status,
+                            ),
+                            None => {
+                                sink.error(format!("Verify phase failed: {}", e));
+                                running.store(false, Ordering::SeqCst);
+                                cancel_flag.store(false, Ordering::SeqCst);
+                                return;
+                            }
+                        },
                     }
                 }
             }
@@ -139,12 +146,17 @@ async fn start_diagnosis(
                 cancel_flag.store(false, Ordering::SeqCst);
                 return;
             }
-            Err(e) => {
-                sink.error(format!("Write phase failed: {}", e));
-                running.store(false, Ordering::SeqCst);
-                cancel_flag.store(false, Ordering::SeqCst);
-                return;
-            }
+            Err(e) => match device_error_status(&e) {
+                Some(status) => {
+                    DiagnosisReport::interrupted(inspector.pattern(), limit_mb * 1024 * 1024, status)
+                }
+                None => {
+                    sink.error(format!("Write phase failed: {}", e));
+                    running.store(false, Ordering::SeqCst);
+                    cancel_flag.store(false, Ordering::SeqCst);
+                    return;
+                }
+            },
         };
 
         if report.total_capacity > 0 {
@@ -1,11 +1,18 @@
 use std::fmt;
+use std::fs;
 use std::io::{self, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use crate::config::AppConfig;
-use crate::core_logic::{DiagnosisReport, DriveHealthStatus};
+use crate::core_logic::{
+    DiagnosisReport, DriveHealthStatus, PatternGenerator, PatternLayout, badblocks_list,
+    builtin_generator, combine_pass_reports, generate_session_seed, inverse_generator,
+};
 use crate::i18n::cli as i18n;
-use crate::io_controller::DriveInspector;
+use crate::io_controller::{
+    AccessOrder, CheckpointPhase, DriveInspector, IoEngine, WriteSummary, device_error_status,
+    is_block_device, segment_path,
+};
 
 pub struct RunOutcome {
     pub bytes_written: u64,
@@ -17,6 +24,7 @@ pub enum RunError {
     QuickProbe(io::Error),
     Write(io::Error),
     Verify(io::Error),
+    Resume(io::Error),
 }
 
 impl fmt::Display for RunError {
@@ -25,6 +33,7 @@ impl fmt::Display for RunError {
             RunError::QuickProbe(err) => write!(f, "quick probe error: {}", err),
             RunError::Write(err) => write!(f, "write phase error: {}", err),
             RunError::Verify(err) => write!(f, "verify phase error: {}", err),
+            RunError::Resume(err) => write!(f, "resume error: {}", err),
         }
     }
 }
@@ -35,6 +44,7 @@ impl std::error::Error for RunError {
             RunError::QuickProbe(err) => Some(err),
             RunError::Write(err) => Some(err),
             RunError::Verify(err) => Some(err),
+            RunError::Resume(err) => Some(err),
         }
     }
 }
@@ -44,46 +54,280 @@ pub fn run_write_verify(
     limit_mb: u64,
     config: AppConfig,
 ) -> Result<RunOutcome, RunError> {
-    let inspector = DriveInspector::with_config(file_path, config);
+    write_and_verify(
+        DriveInspector::with_config(file_path, config),
+        limit_mb,
+        config,
+    )
+}
+
+// Writes and verifies the same range once per generator, in the spirit of
+// `badblocks -w`. Every pass shares one session seed, so an inverted generator
+// writes the exact complement of the pass it follows.
+pub fn run_passes(
+    file_path: &str,
+    limit_mb: u64,
+    config: AppConfig,
+    generators: &[&'static dyn PatternGenerator],
+) -> Result<RunOutcome, RunError> {
+    let session_seed = generate_session_seed();
+    let mut reports = Vec::new();
+    let mut bytes_written = 0;
+    for (index, &generator) in generators.iter().enumerate() {
+        println!(
+            "[INFO] Pass {} of {}: pattern {}.",
+            index + 1,
+            generators.len(),
+            generator.name()
+        );
+        // Once the first pass has found the capacity real, later probes
+        // would only repeat it.
+        let config = AppConfig {
+            pattern_generator: generator,
+            quick_probe_enabled: config.quick_probe_enabled && index == 0,
+            ..config
+        };
+        let inspector =
+            DriveInspector::with_config(file_path, config).with_session_seed(session_seed);
+        let outcome = write_and_verify(inspector, limit_mb, config)?;
+        bytes_written = bytes_written.max(outcome.bytes_written);
+        // Nothing written, a failed probe or a lost device leaves nothing for
+        // further passes to add.
+        let finished = outcome.report.tested_bytes > 0
+            && outcome.report.tested_bytes == outcome.report.total_capacity;
+        reports.push(outcome.report);
+        if !finished {
+            break;
+        }
+    }
+    match combine_pass_reports(&reports) {
+        Some(report) => Ok(RunOutcome {
+            bytes_written,
+            report,
+        }),
+        None => Err(RunError::Write(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "No passes to run.",
+        ))),
+    }
+}
+
+fn write_and_verify(
+    inspector: DriveInspector,
+    limit_mb: u64,
+    config: AppConfig,
+) -> Result<RunOutcome, RunError> {
     if config.quick_probe_enabled {
-        if let Some(report) = inspector
-            .run_quick_probe_phase(limit_mb, config.quick_probe_steps)
-            .map_err(RunError::QuickProbe)?
-        {
-            return Ok(RunOutcome {
-                bytes_written: report.tested_bytes,
-                report,
-            });
-        }
-    }
-    let bytes_written = inspector
-        .run_write_phase(limit_mb)
-        .map_err(RunError::Write)?;
+        match inspector.run_quick_probe_phase(limit_mb, config.quick_probe_steps) {
+            Ok(Some(report)) => {
+                return Ok(RunOutcome {
+                    bytes_written: report.tested_bytes,
+                    report,
+                });
+            }
+            Ok(None) => {}
+            Err(e) => return interrupted(&inspector, limit_mb, 0, e, RunError::QuickProbe),
+        }
+    }
+    let written = match inspector.run_write_phase(limit_mb) {
+        Ok(written) => written,
+        Err(e) => return interrupted(&inspector, limit_mb, 0, e, RunError::Write),
+    };
+    let bytes_written = written.bytes_written;
     if bytes_written == 0 {
-        let report = DiagnosisReport {
-            total_capacity: 0,
-            tested_bytes: 0,
-            valid_bytes: 0,
-            error_count: 0,
-            health_score: 0.0,
-            status: DriveHealthStatus::DataLoss,
-            conclusion: "No data written; verification skipped.".to_string(),
-        };
+        let report = DiagnosisReport::no_data(
+            inspector.pattern(),
+            "No data written; verification skipped.".to_string(),
+        );
         return Ok(RunOutcome {
             bytes_written,
             report,
         });
     }
 
-    let report = inspector
-        .run_verify_phase(bytes_written)
-        .map_err(RunError::Verify)?;
+    let report = match inspector.run_verify_phase(&written) {
+        Ok(report) => report,
+        Err(e) => return interrupted(&inspector, limit_mb, bytes_written, e, RunError::Verify),
+    };
     Ok(RunOutcome {
         bytes_written,
         report,
     })
 }
 
+// Checks data left by an earlier run without writing anything. The manifest
+// supplies the pattern and length; without one the session seed has to come
+// from the caller and the length from the target itself.
+pub fn run_verify_only(
+    file_path: &str,
+    limit_mb: u64,
+    config: AppConfig,
+    session_seed: Option<u64>,
+) -> Result<RunOutcome, RunError> {
+    let inspector = DriveInspector::with_config(file_path, config);
+    let (inspector, total_bytes) = match inspector.read_manifest() {
+        Ok(Some(manifest)) => {
+            println!(
+                "[INFO] Manifest found: {} bytes, pattern {}.",
+                manifest.bytes_written,
+                manifest.pattern.generator.name()
+            );
+            (inspector.with_manifest(&manifest), manifest.bytes_written)
+        }
+        Ok(None) => {
+            let Some(seed) = session_seed else {
+                return Err(RunError::Verify(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "No manifest found; a session seed is required.",
+                )));
+            };
+            match inspector.target_length() {
+                Ok(length) => (inspector.with_session_seed(seed), length),
+                Err(e) => return interrupted(&inspector, limit_mb, 0, e, RunError::Verify),
+            }
+        }
+        Err(e) => return Err(RunError::Verify(e)),
+    };
+    let total_bytes = if limit_mb == 0 {
+        total_bytes
+    } else {
+        total_bytes.min(limit_mb * 1024 * 1024)
+    };
+    if total_bytes == 0 {
+        let report =
+            DiagnosisReport::no_data(inspector.pattern(), "No data to verify.".to_string());
+        return Ok(RunOutcome {
+            bytes_written: 0,
+            report,
+        });
+    }
+    println!(
+        "[INFO] Verifying existing data. Session={:016x}",
+        inspector.session_seed()
+    );
+    let existing = WriteSummary {
+        bytes_written: total_bytes,
+        ..WriteSummary::default()
+    };
+    let report = match inspector.run_verify_phase(&existing) {
+        Ok(report) => report,
+        Err(e) => return interrupted(&inspector, limit_mb, total_bytes, e, RunError::Verify),
+    };
+    Ok(RunOutcome {
+        bytes_written: total_bytes,
+        report,
+    })
+}
+
+// Picks up the run a checkpoint was saved for: an unfinished write carries on
+// and is then verified in full, an unfinished verify carries on from where
+// each region stopped.
+pub fn resume_run(file_path: &str, config: AppConfig) -> Result<RunOutcome, RunError> {
+    let inspector = DriveInspector::with_config(file_path, config);
+    let checkpoint = match inspector.read_checkpoint() {
+        Ok(Some(checkpoint)) => checkpoint,
+        Ok(None) => {
+            return Err(RunError::Resume(io::Error::new(
+                io::ErrorKind::NotFound,
+                "No checkpoint to resume.",
+            )));
+        }
+        Err(e) => return Err(RunError::Resume(e)),
+    };
+    let inspector = inspector
+        .with_checkpoint(&checkpoint)
+        .map_err(RunError::Resume)?;
+    let limit_mb = checkpoint.bytes_written() / 1024 / 1024;
+
+    let report = if checkpoint.phase() == CheckpointPhase::Write {
+        let written = match inspector.resume_write_phase(&checkpoint) {
+            Ok(written) => written,
+            Err(e) => return interrupted(&inspector, limit_mb, 0, e, RunError::Write),
+        };
+        match inspector.run_verify_phase(&written) {
+            Ok(report) => report,
+            Err(e) => {
+                let bytes_written = written.bytes_written;
+                return interrupted(&inspector, limit_mb, bytes_written, e, RunError::Verify);
+            }
+        }
+    } else {
+        let bytes_written = checkpoint.bytes_written();
+        match inspector.resume_verify_phase(&checkpoint) {
+            Ok(report) => report,
+            Err(e) => return interrupted(&inspector, limit_mb, bytes_written, e, RunError::Verify),
+        }
+    };
+    Ok(RunOutcome {
+        bytes_written: report.tested_bytes,
+        report,
+    })
+}
+
+// Tests a device without losing what is on it; see
+// `DriveInspector::run_in_place_phase`.
+pub fn run_in_place(
+    file_path: &str,
+    limit_mb: u64,
+    config: AppConfig,
+    journal: &Path,
+) -> Result<RunOutcome, RunError> {
+    let inspector = DriveInspector::with_config(file_path, config);
+    let report = match inspector.run_in_place_phase(limit_mb, journal) {
+        Ok(report) => report,
+        Err(e) => return interrupted(&inspector, limit_mb, 0, e, RunError::Write),
+    };
+    Ok(RunOutcome {
+        bytes_written: report.tested_bytes,
+        report,
+    })
+}
+
+// Generator names separated by commas. `inverse` stands for the complement of
+// the pass before it, or of `base` when it comes first.
+fn parse_pass_plan(
+    value: &str,
+    base: &'static dyn PatternGenerator,
+) -> Option<Vec<&'static dyn PatternGenerator>> {
+    let mut plan: Vec<&'static dyn PatternGenerator> = Vec::new();
+    for name in value.split(',') {
+        let generator = if name == "inverse" {
+            inverse_generator(plan.last().copied().unwrap_or(base))?
+        } else {
+            builtin_generator(name)?
+        };
+        plan.push(generator);
+    }
+    Some(plan)
+}
+
+// Kept off the device under test, in the working directory.
+fn default_journal_path(target: &Path) -> PathBuf {
+    let name = target
+        .file_name()
+        .map_or("target".into(), |name| name.to_string_lossy());
+    PathBuf::from(format!("truthbyte-{}.journal", name))
+}
+
+// A device that is write-protected or gone is a finding, not a tool failure, so
+// it becomes a report instead of an error.
+fn interrupted(
+    inspector: &DriveInspector,
+    limit_mb: u64,
+    bytes_written: u64,
+    error: io::Error,
+    wrap: fn(io::Error) -> RunError,
+) -> Result<RunOutcome, RunError> {
+    let Some(status) = device_error_status(&error) else {
+        return Err(wrap(error));
+    };
+    println!("[ERROR] {}", error);
+    Ok(RunOutcome {
+        bytes_written,
+        report: DiagnosisReport::interrupted(inspector.pattern(), limit_mb * 1024 * 1024, status),
+    })
+}
+
 pub fn print_diagnostic_summary(report: &DiagnosisReport) {
     let tested_mb = report.tested_bytes as f64 / (1024.0 * 1024.0);
     let valid_mb = report.valid_bytes as f64 / (1024.0 * 1024.0);
@@ -107,6 +351,82 @@ pub fn print_diagnostic_summary(report: &DiagnosisReport) {
     println!("{health_label} : {:.1} / 100.0", report.health_score);
     println!("{tested_label} : {:.1} / {:.1} MB", tested_mb, valid_mb);
     println!("{total_label} : {:.1} MB", total_mb);
+    if report.bit_errors.flipped_bits > 0 {
+        let bits = &report.bit_errors;
+        println!(
+            "{} : {} (0->1: {}, 1->0: {}, BER: {:.2e})",
+            i18n::bit_errors_label(locale),
+            bits.flipped_bits,
+            bits.zero_to_one,
+            bits.one_to_zero,
+            bits.bit_error_rate
+        );
+    }
+    for (kind, latency) in [
+        ("write", &report.write_latency),
+        ("read", &report.read_latency),
+    ] {
+        let Some(mean_us) = latency.mean_us() else {
+            continue;
+        };
+        let slowest = latency.slowest.first().map_or(0, |io| io.offset);
+        println!(
+            "{} : {} mean {:.1} ms, max {:.1} ms at 0x{:X}, stalls {}",
+            i18n::latency_label(locale),
+            kind,
+            mean_us as f64 / 1000.0,
+            latency.max_us as f64 / 1000.0,
+            slowest,
+            latency.stalls
+        );
+    }
+    if let Some(cache) = &report.write_cache {
+        println!(
+            "{} : {:.1} MB at {:.1} MB/s, then {:.1} MB/s sustained",
+            i18n::write_cache_label(locale),
+            cache.cache_bytes as f64 / 1024.0 / 1024.0,
+            cache.cached_mbps,
+            cache.sustained_mbps
+        );
+    }
+    if let Some(geometry) = &report.geometry {
+        let size = |value: Option<u32>| value.map_or("-".to_string(), |value| value.to_string());
+        println!(
+            "{} : logical {}, physical {}, alignment {} bytes",
+            i18n::geometry_label(locale),
+            size(geometry.logical_sector_size),
+            size(geometry.physical_sector_size),
+            geometry.alignment
+        );
+    }
+    if !report.passes.is_empty() {
+        println!("{} : {}", i18n::passes_label(locale), report.passes.len());
+        for (index, pass) in report.passes.iter().enumerate() {
+            println!(
+                "  {}. {}: {}, errors {}",
+                index + 1,
+                pass.pattern_generator,
+                i18n::status_label(locale, pass.status),
+                pass.error_count
+            );
+        }
+    }
+    if !report.bad_ranges.is_empty() {
+        let bad_bytes: u64 = report
+            .bad_ranges
+            .iter()
+            .map(|range| range.end - range.start)
+            .sum();
+        println!(
+            "{} : {} ({} bytes)",
+            i18n::bad_ranges_label(locale),
+            report.bad_ranges.len(),
+            bad_bytes
+        );
+        for range in report.bad_ranges.iter().take(5) {
+            println!("  0x{:X}-0x{:X} {:?}", range.start, range.end, range.kind);
+        }
+    }
     println!("{status_header}       : {}", status_label);
     println!("{conclusion_header}   : {}", conclusion);
     println!("========================================");
@@ -125,12 +445,194 @@ pub fn run_cli(args: &[String]) -> i32 {
     let file_path = &args[1];
     let mut limit_arg: Option<&str> = None;
     let mut force = false;
+    let mut badblocks_path: Option<&str> = None;
+    let mut badblocks_block_size: Option<u64> = None;
+    let mut verify_only = false;
+    let mut session_seed: Option<u64> = None;
+    let mut in_place = false;
+    let mut resume = false;
+    let mut rollback = false;
+    let mut journal_arg: Option<&str> = None;
+    let mut passes_arg: Option<&str> = None;
+    let mut config = AppConfig::default();
 
     for arg in args.iter().skip(2) {
         if arg == "--force" || arg == "-f" {
             force = true;
             continue;
         }
+        if arg == "--resume" {
+            resume = true;
+            continue;
+        }
+        if arg == "--in-place" {
+            in_place = true;
+            continue;
+        }
+        if arg == "--rollback" {
+            rollback = true;
+            continue;
+        }
+        if let Some(path) = arg.strip_prefix("--journal=") {
+            journal_arg = Some(path);
+            continue;
+        }
+        if let Some(value) = arg.strip_prefix("--passes=") {
+            passes_arg = Some(value);
+            continue;
+        }
+        if arg == "--verify-only" {
+            verify_only = true;
+            continue;
+        }
+        if let Some(value) = arg.strip_prefix("--seed=") {
+            match u64::from_str_radix(value.trim_start_matches("0x"), 16) {
+                Ok(seed) => session_seed = Some(seed),
+                Err(_) => {
+                    let message = i18n::cli_invalid_seed(locale);
+                    eprintln!("[ERROR] {}: {}", message, value);
+                    return 2;
+                }
+            }
+            continue;
+        }
+        if arg == "--device" {
+            config.raw_device = true;
+            continue;
+        }
+        if arg == "--multi-file" {
+            config.multi_file = true;
+            continue;
+        }
+        if arg == "--sector-headers" {
+            config.pattern_layout = PatternLayout::SectorHeaders;
+            continue;
+        }
+        if let Some(path) = arg.strip_prefix("--badblocks=") {
+            badblocks_path = Some(path);
+            continue;
+        }
+        if let Some(value) = arg.strip_prefix("--badblocks-block-size=") {
+            match value.parse::<u64>() {
+                Ok(size) if size > 0 => badblocks_block_size = Some(size),
+                _ => {
+                    let message = i18n::cli_invalid_badblocks_block_size(locale);
+                    eprintln!("[ERROR] {}: {}", message, value);
+                    return 2;
+                }
+            }
+            continue;
+        }
+        if let Some(name) = arg.strip_prefix("--engine=") {
+            match IoEngine::from_name(name) {
+                Some(engine) => config.io_engine = engine,
+                None => {
+                    let message = i18n::cli_unknown_engine(locale);
+                    eprintln!("[ERROR] {}: {}", message, name);
+                    return 2;
+                }
+            }
+            continue;
+        }
+        if let Some(name) = arg.strip_prefix("--write-order=") {
+            match AccessOrder::from_name(name) {
+                Some(order) => config.write_order = order,
+                None => {
+                    let message = i18n::cli_unknown_order(locale);
+                    eprintln!("[ERROR] {}: {}", message, name);
+                    return 2;
+                }
+            }
+            continue;
+        }
+        if let Some(name) = arg.strip_prefix("--verify-order=") {
+            match AccessOrder::from_name(name) {
+                Some(order) => config.verify_order = order,
+                None => {
+                    let message = i18n::cli_unknown_order(locale);
+                    eprintln!("[ERROR] {}: {}", message, name);
+                    return 2;
+                }
+            }
+            continue;
+        }
+        if let Some(value) = arg.strip_prefix("--queue-depth=") {
+            match value.parse::<usize>() {
+                Ok(depth) if depth > 0 => config.queue_depth = depth,
+                _ => {
+                    let message = i18n::cli_invalid_queue_depth(locale);
+                    eprintln!("[ERROR] {}: {}", message, value);
+                    return 2;
+                }
+            }
+            continue;
+        }
+        if let Some(value) = arg.strip_prefix("--verify-threads=") {
+            match value.parse::<usize>() {
+                Ok(threads) if threads > 0 => config.verify_threads = threads,
+                _ => {
+                    let message = i18n::cli_invalid_verify_threads(locale);
+                    eprintln!("[ERROR] {}: {}", message, value);
+                    return 2;
+                }
+            }
+            continue;
+        }
+        if let Some(value) = arg.strip_prefix("--segment-mb=") {
+            match value.parse::<u64>() {
+                Ok(size) if size > 0 => config.segment_mb = size,
+                _ => {
+                    let message = i18n::cli_invalid_segment_size(locale);
+                    eprintln!("[ERROR] {}: {}", message, value);
+                    return 2;
+                }
+            }
+            continue;
+        }
+        if let Some(value) = arg.strip_prefix("--reserve-mb=") {
+            match value.parse::<u64>() {
+                Ok(size) => config.reserve_mb = size,
+                Err(_) => {
+                    let message = i18n::cli_invalid_reserve(locale);
+                    eprintln!("[ERROR] {}: {}", message, value);
+                    return 2;
+                }
+            }
+            continue;
+        }
+        if let Some(value) = arg.strip_prefix("--stall-secs=") {
+            match value.parse::<u64>() {
+                Ok(seconds) => config.stall_secs = seconds,
+                Err(_) => {
+                    let message = i18n::cli_invalid_stall(locale);
+                    eprintln!("[ERROR] {}: {}", message, value);
+                    return 2;
+                }
+            }
+            continue;
+        }
+        if let Some(value) = arg.strip_prefix("--curve-region-mb=") {
+            match value.parse::<u64>() {
+                Ok(size) if size > 0 => config.curve_region_mb = size,
+                _ => {
+                    let message = i18n::cli_invalid_curve_region(locale);
+                    eprintln!("[ERROR] {}: {}", message, value);
+                    return 2;
+                }
+            }
+            continue;
+        }
+        if let Some(name) = arg.strip_prefix("--pattern=") {
+            match builtin_generator(name) {
+                Some(generator) => config.pattern_generator = generator,
+                None => {
+                    let message = i18n::cli_unknown_pattern(locale);
+                    eprintln!("[ERROR] {}: {}", message, name);
+                    return 2;
+                }
+            }
+            continue;
+        }
 
         if limit_arg.is_none() {
             limit_arg = Some(arg.as_str());
@@ -153,15 +655,79 @@ pub fn run_cli(args: &[String]) -> i32 {
         None => 0,
     };
 
+    // Resolved after every flag is in, since `inverse` may refer to --pattern.
+    let passes = match passes_arg {
+        Some(value) => match parse_pass_plan(value, config.pattern_generator) {
+            Some(plan) => Some(plan),
+            None => {
+                let message = i18n::cli_invalid_passes(locale);
+                eprintln!("[ERROR] {}: {}", message, value);
+                return 2;
+            }
+        },
+        None => None,
+    };
+    // Each of these runs a single pass of its own and would silently drop the
+    // plan.
+    if passes.is_some() && (verify_only || resume || in_place) {
+        let message = i18n::cli_passes_conflict(locale);
+        eprintln!("[ERROR] {}", message);
+        return 2;
+    }
+
     let path = Path::new(file_path);
-    if path.is_dir() {
+    if config.multi_file && !path.is_dir() {
+        let message = i18n::cli_target_not_dir(locale);
+        eprintln!("[ERROR] {}: {}", message, file_path);
+        return 2;
+    }
+    if !config.multi_file && path.is_dir() {
         let message = i18n::cli_target_is_dir(locale);
         eprintln!("[ERROR] {}: {}", message, file_path);
         return 2;
     }
+    // Block numbers only mean something to badblocks and e2fsck when the
+    // offsets are device offsets.
+    if badblocks_path.is_some() && !(config.raw_device || is_block_device(path)) {
+        let message = i18n::cli_badblocks_needs_device(locale);
+        eprintln!("[ERROR] {}: {}", message, file_path);
+        return 2;
+    }
+
+    let journal = journal_arg.map_or_else(|| default_journal_path(path), PathBuf::from);
+    if rollback {
+        let inspector = DriveInspector::with_config(file_path, config);
+        return match inspector.roll_back_in_place(&journal) {
+            Ok(Some(_)) => 0,
+            Ok(None) => {
+                let message = i18n::cli_rollback_nothing(locale);
+                println!("[INFO] {}: {}", message, journal.display());
+                0
+            }
+            Err(e) => {
+                let message = i18n::cli_rollback_failed(locale);
+                eprintln!("[ERROR] {}: {}", message, e);
+                2
+            }
+        };
+    }
 
-    if path.exists() && !force {
-        print!("{}", i18n::cli_overwrite_prompt(locale, file_path));
+    // In multi-file mode the existing series is what gets overwritten.
+    let overwritten = if config.multi_file {
+        segment_path(path, 0)
+    } else {
+        path.to_path_buf()
+    };
+    if (verify_only || in_place || resume) && !overwritten.exists() {
+        let message = i18n::cli_target_missing(locale);
+        eprintln!("[ERROR] {}: {}", message, overwritten.display());
+        return 2;
+    }
+    if !verify_only && !in_place && !resume && overwritten.exists() && !force {
+        print!(
+            "{}",
+            i18n::cli_overwrite_prompt(locale, &overwritten.display().to_string())
+        );
         if io::stdout().flush().is_err() {
             let message = i18n::cli_prompt_flush_failed(locale);
             eprintln!("[ERROR] {}", message);
@@ -181,15 +747,48 @@ pub fn run_cli(args: &[String]) -> i32 {
         }
     }
 
-    match run_write_verify(file_path, limit_mb, AppConfig::default()) {
+    let result = if verify_only {
+        run_verify_only(file_path, limit_mb, config, session_seed)
+    } else if resume {
+        resume_run(file_path, config)
+    } else if in_place {
+        run_in_place(file_path, limit_mb, config, &journal)
+    } else if let Some(passes) = &passes {
+        run_passes(file_path, limit_mb, config, passes)
+    } else {
+        run_write_verify(file_path, limit_mb, config)
+    };
+    match result {
         Ok(outcome) => {
-            if outcome.bytes_written == 0 {
+            if outcome.bytes_written == 0 && outcome.report.status == DriveHealthStatus::DataLoss {
                 let message = i18n::cli_no_data_written(locale);
                 println!("[ERROR] {}", message);
                 return 2;
             }
 
             print_diagnostic_summary(&outcome.report);
+            if let Some(path) = badblocks_path {
+                let Some(block_size) = badblocks_block_size.or_else(|| {
+                    let geometry = outcome.report.geometry?;
+                    Some(
+                        geometry
+                            .logical_sector_size
+                            .map_or(geometry.alignment, u64::from),
+                    )
+                }) else {
+                    let message = i18n::cli_badblocks_block_size_unknown(locale);
+                    eprintln!("[ERROR] {}", message);
+                    return 2;
+                };
+                let list = badblocks_list(&outcome.report.bad_ranges, block_size);
+                if let Err(e) = fs::write(path, list) {
+                    let message = i18n::cli_badblocks_write_failed(locale);
+                    eprintln!("[ERROR] {}: {}", message, e);
+                    return 2;
+                }
+                let message = i18n::cli_badblocks_written(locale);
+                println!("[INFO] {}: {} (-b {})", message, path, block_size);
+            }
             if outcome.report.status == DriveHealthStatus::Healthy {
                 0
             } else {
@@ -211,5 +810,10 @@ pub fn run_cli(args: &[String]) -> i32 {
             println!("[ERROR] {}: {}", message, e);
             2
         }
+        Err(RunError::Resume(e)) => {
+            let message = i18n::cli_resume_failed(locale);
+            println!("[ERROR] {}: {}", message, e);
+            2
+        }
     }
 }
@@ -1,8 +1,25 @@
+use crate::core_logic::{PatternGenerator, PatternLayout, SPLITMIX};
+use crate::io_controller::{AccessOrder, IoEngine};
+
 #[derive(Clone, Copy, Debug)]
 pub struct AppConfig {
     pub block_size: usize,
     pub quick_probe_enabled: bool,
     pub quick_probe_steps: usize,
+    pub pattern_layout: PatternLayout,
+    pub pattern_generator: &'static dyn PatternGenerator,
+    pub io_engine: IoEngine,
+    pub queue_depth: usize,
+    pub worker_threads: usize,
+    pub verify_threads: usize,
+    pub raw_device: bool,
+    pub multi_file: bool,
+    pub segment_mb: u64,
+    pub reserve_mb: u64,
+    pub write_order: AccessOrder,
+    pub verify_order: AccessOrder,
+    pub stall_secs: u64,
+    pub curve_region_mb: u64,
 }
 
 impl Default for AppConfig {
@@ -11,6 +28,20 @@ impl Default for AppConfig {
             block_size: 4 * 1024 * 1024,
             quick_probe_enabled: true,
             quick_probe_steps: 100,
+            pattern_layout: PatternLayout::Plain,
+            pattern_generator: &SPLITMIX,
+            io_engine: IoEngine::Blocking,
+            queue_depth: 8,
+            worker_threads: 2,
+            verify_threads: 1,
+            raw_device: false,
+            multi_file: false,
+            segment_mb: 4095,
+            reserve_mb: 16,
+            write_order: AccessOrder::Sequential,
+            verify_order: AccessOrder::Sequential,
+            stall_secs: 10,
+            curve_region_mb: 64,
         }
     }
 }
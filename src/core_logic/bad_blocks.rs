@@ -0,0 +1,158 @@
+use std::fmt::Write;
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BadRangeKind {
+    Mismatch,
+    ReadError,
+    WriteError,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct BadRange {
+    pub start: u64,
+    pub end: u64,
+    pub kind: BadRangeKind,
+}
+
+// Extents of one block arrive in offset order, so they coalesce with the last
+// range. Blocks themselves can come in any order and from several regions;
+// `sorted_bad_ranges` puts the whole list in order once the scan is done.
+pub(super) fn push_bad_range(ranges: &mut Vec<BadRange>, start: u64, end: u64, kind: BadRangeKind) {
+    if start >= end {
+        return;
+    }
+    match ranges.last_mut() {
+        Some(last) if last.kind == kind && last.end >= start && last.start <= end => {
+            last.start = last.start.min(start);
+            last.end = last.end.max(end);
+        }
+        _ => ranges.push(BadRange { start, end, kind }),
+    }
+}
+
+// Sorted by offset, with overlapping or touching ranges of the same kind
+// merged into one.
+pub(super) fn sorted_bad_ranges<'a>(
+    ranges: impl IntoIterator<Item = &'a BadRange>,
+) -> Vec<BadRange> {
+    let mut by_kind: Vec<BadRange> = ranges.into_iter().copied().collect();
+    by_kind.sort_unstable_by_key(|range| (range.kind as u8, range.start, range.end));
+    let mut merged = Vec::with_capacity(by_kind.len());
+    for range in by_kind {
+        push_bad_range(&mut merged, range.start, range.end, range.kind);
+    }
+    merged.sort_unstable_by_key(|range| (range.start, range.end));
+    merged
+}
+
+// One block number per line, as written by e2fsprogs `badblocks -o` and read by
+// `mke2fs -l` / `e2fsck -l`. The block size must match the one passed to those
+// tools with `-b`.
+pub fn badblocks_list(ranges: &[BadRange], block_size: u64) -> String {
+    let block_size = block_size.max(1);
+    let mut blocks: Vec<(u64, u64)> = ranges
+        .iter()
+        .map(|range| (range.start / block_size, range.end.div_ceil(block_size)))
+        .collect();
+    blocks.sort_unstable();
+
+    let mut output = String::new();
+    let mut next = 0;
+    for (first, end) in blocks {
+        for block in first.max(next)..end {
+            let _ = writeln!(output, "{block}");
+        }
+        next = next.max(end);
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BadRange, BadRangeKind, badblocks_list, push_bad_range, sorted_bad_ranges};
+
+    #[test]
+    fn test_adjacent_ranges_of_same_kind_coalesce() {
+        let mut ranges = Vec::new();
+        push_bad_range(&mut ranges, 0, 10, BadRangeKind::Mismatch);
+        push_bad_range(&mut ranges, 10, 20, BadRangeKind::Mismatch);
+        push_bad_range(&mut ranges, 20, 30, BadRangeKind::ReadError);
+        push_bad_range(&mut ranges, 40, 50, BadRangeKind::ReadError);
+        assert_eq!(
+            ranges,
+            vec![
+                BadRange {
+                    start: 0,
+                    end: 20,
+                    kind: BadRangeKind::Mismatch,
+                },
+                BadRange {
+                    start: 20,
+                    end: 30,
+                    kind: BadRangeKind::ReadError,
+                },
+                BadRange {
+                    start: 40,
+                    end: 50,
+                    kind: BadRangeKind::ReadError,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reverse_order_ranges_are_sorted_and_merged() {
+        let mut ranges = Vec::new();
+        push_bad_range(&mut ranges, 40, 50, BadRangeKind::Mismatch);
+        push_bad_range(&mut ranges, 30, 40, BadRangeKind::ReadError);
+        push_bad_range(&mut ranges, 20, 30, BadRangeKind::Mismatch);
+        push_bad_range(&mut ranges, 10, 25, BadRangeKind::Mismatch);
+        push_bad_range(&mut ranges, 0, 5, BadRangeKind::ReadError);
+        assert_eq!(
+            sorted_bad_ranges(&ranges),
+            vec![
+                BadRange {
+                    start: 0,
+                    end: 5,
+                    kind: BadRangeKind::ReadError,
+                },
+                BadRange {
+                    start: 10,
+                    end: 30,
+                    kind: BadRangeKind::Mismatch,
+                },
+                BadRange {
+                    start: 30,
+                    end: 40,
+                    kind: BadRangeKind::ReadError,
+                },
+                BadRange {
+                    start: 40,
+                    end: 50,
+                    kind: BadRangeKind::Mismatch,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_badblocks_list_rounds_to_blocks_once() {
+        let ranges = [
+            BadRange {
+                start: 100,
+                end: 5000,
+                kind: BadRangeKind::Mismatch,
+            },
+            BadRange {
+                start: 6000,
+                end: 8193,
+                kind: BadRangeKind::ReadError,
+            },
+        ];
+        assert_eq!(badblocks_list(&ranges, 4096), "0\n1\n2\n");
+        assert_eq!(badblocks_list(&[], 4096), "");
+    }
+}
@@ -0,0 +1,181 @@
+use serde::Serialize;
+
+use super::SECTOR_SIZE;
+
+const SECTOR_BUCKETS: [u64; 4] = [2, 8, 64, 512];
+const MIN_STUCK_FLIPS: u64 = 16;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct BitPositionErrors {
+    pub zero_to_one: u64,
+    pub one_to_zero: u64,
+}
+
+impl BitPositionErrors {
+    fn total(&self) -> u64 {
+        self.zero_to_one + self.one_to_zero
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct StuckBit {
+    pub position: u8,
+    pub stuck_at: u8,
+}
+
+// `sector_histogram` buckets sectors by flipped bits: 1, 2-7, 8-63, 64-511 and
+// 512 or more. A few bits per sector points at worn cells; bursts point at the
+// controller or the link.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BitErrorStats {
+    pub flipped_bits: u64,
+    pub zero_to_one: u64,
+    pub one_to_zero: u64,
+    pub zero_to_one_ratio: Option<f64>,
+    pub positions: [BitPositionErrors; 8],
+    pub stuck_bits: Vec<StuckBit>,
+    pub sectors_with_errors: u64,
+    pub max_bits_per_sector: u64,
+    pub sector_histogram: [u64; 5],
+    pub bit_error_rate: f64,
+}
+
+impl BitErrorStats {
+    pub fn record(&mut self, offset: u64, expected: &[u8], actual: &[u8]) {
+        let len = expected.len().min(actual.len());
+        let mut sector_start = 0;
+        while sector_start < len {
+            let position = offset + sector_start as u64;
+            let sector_len = SECTOR_SIZE - (position % SECTOR_SIZE as u64) as usize;
+            let sector_end = (sector_start + sector_len).min(len);
+            let flipped = self.record_sector(
+                &expected[sector_start..sector_end],
+                &actual[sector_start..sector_end],
+            );
+            if flipped > 0 {
+                self.sectors_with_errors += 1;
+                self.max_bits_per_sector = self.max_bits_per_sector.max(flipped);
+                let bucket = SECTOR_BUCKETS
+                    .iter()
+                    .position(|&limit| flipped < limit)
+                    .unwrap_or(SECTOR_BUCKETS.len());
+                self.sector_histogram[bucket] += 1;
+            }
+            sector_start = sector_end;
+        }
+    }
+
+    fn record_sector(&mut self, expected: &[u8], actual: &[u8]) -> u64 {
+        let mut flipped = 0;
+        for (&want, &got) in expected.iter().zip(actual) {
+            let diff = want ^ got;
+            if diff == 0 {
+                continue;
+            }
+            let rising = diff & got;
+            let falling = diff & want;
+            flipped += diff.count_ones() as u64;
+            self.zero_to_one += rising.count_ones() as u64;
+            self.one_to_zero += falling.count_ones() as u64;
+            for (bit, position) in self.positions.iter_mut().enumerate() {
+                position.zero_to_one += ((rising >> bit) & 1) as u64;
+                position.one_to_zero += ((falling >> bit) & 1) as u64;
+            }
+        }
+        self.flipped_bits += flipped;
+        flipped
+    }
+
+    // Only the raw counters are combined; `finalize` derives the rest once all
+    // parts are in.
+    pub fn merge(&mut self, other: &BitErrorStats) {
+        self.flipped_bits += other.flipped_bits;
+        self.zero_to_one += other.zero_to_one;
+        self.one_to_zero += other.one_to_zero;
+        for (position, errors) in self.positions.iter_mut().zip(&other.positions) {
+            position.zero_to_one += errors.zero_to_one;
+            position.one_to_zero += errors.one_to_zero;
+        }
+        self.sectors_with_errors += other.sectors_with_errors;
+        self.max_bits_per_sector = self.max_bits_per_sector.max(other.max_bits_per_sector);
+        for (bucket, count) in self.sector_histogram.iter_mut().zip(other.sector_histogram) {
+            *bucket += count;
+        }
+    }
+
+    // A bit position counts as stuck when it collects at least twice its share
+    // of the errors and nearly all of them pull it the same way.
+    pub fn finalize(&mut self, tested_bytes: u64) {
+        self.bit_error_rate = if tested_bytes == 0 {
+            0.0
+        } else {
+            self.flipped_bits as f64 / (tested_bytes as f64 * 8.0)
+        };
+        self.zero_to_one_ratio =
+            (self.one_to_zero > 0).then(|| self.zero_to_one as f64 / self.one_to_zero as f64);
+
+        let average = self.flipped_bits as f64 / 8.0;
+        self.stuck_bits = self
+            .positions
+            .iter()
+            .enumerate()
+            .filter(|(_, errors)| {
+                errors.total() >= MIN_STUCK_FLIPS && errors.total() as f64 >= 2.0 * average
+            })
+            .filter_map(|(position, errors)| {
+                let dominant = errors.zero_to_one.max(errors.one_to_zero);
+                if dominant * 20 < errors.total() * 19 {
+                    return None;
+                }
+                Some(StuckBit {
+                    position: position as u8,
+                    stuck_at: u8::from(errors.zero_to_one > errors.one_to_zero),
+                })
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BitErrorStats, StuckBit};
+    use crate::core_logic::SECTOR_SIZE;
+
+    #[test]
+    fn test_counts_flip_directions_per_sector() {
+        let expected = vec![0b1010_1010u8; 2 * SECTOR_SIZE];
+        let mut actual = expected.clone();
+        actual[0] = 0b1010_1011;
+        actual[SECTOR_SIZE + 1] = 0b0000_0000;
+
+        let mut stats = BitErrorStats::default();
+        stats.record(0, &expected, &actual);
+        stats.finalize(expected.len() as u64);
+
+        assert_eq!(stats.flipped_bits, 5);
+        assert_eq!(stats.zero_to_one, 1);
+        assert_eq!(stats.one_to_zero, 4);
+        assert_eq!(stats.zero_to_one_ratio, Some(0.25));
+        assert_eq!(stats.sectors_with_errors, 2);
+        assert_eq!(stats.max_bits_per_sector, 4);
+        assert_eq!(stats.sector_histogram, [1, 1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_detects_stuck_bit_position() {
+        let expected = vec![0u8; 64];
+        let actual = vec![0b0001_0000u8; 64];
+
+        let mut stats = BitErrorStats::default();
+        stats.record(0, &expected, &actual);
+        stats.finalize(expected.len() as u64);
+
+        assert_eq!(
+            stats.stuck_bits,
+            vec![StuckBit {
+                position: 4,
+                stuck_at: 1,
+            }]
+        );
+    }
+}
@@ -0,0 +1,311 @@
+use serde::Serialize;
+
+use super::{DriveHealthStatus, Pattern, SECTOR_SIZE, decode_sector_header, first_sector_index};
+
+const MAX_FINGERPRINTS: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureKind {
+    ZeroFill,
+    OnesFill,
+    RepeatedBlock,
+    MisplacedData,
+    StaleSession,
+    ForeignData,
+    BitCorruption,
+}
+
+impl FailureKind {
+    pub fn status(self) -> DriveHealthStatus {
+        match self {
+            FailureKind::ZeroFill
+            | FailureKind::OnesFill
+            | FailureKind::RepeatedBlock
+            | FailureKind::MisplacedData => DriveHealthStatus::FakeCapacity,
+            FailureKind::StaleSession | FailureKind::ForeignData => DriveHealthStatus::StaleData,
+            FailureKind::BitCorruption => DriveHealthStatus::PhysicalCorruption,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct FailureBreakdown {
+    pub zero_fill: u64,
+    pub ones_fill: u64,
+    pub repeated_block: u64,
+    pub misplaced_data: u64,
+    pub stale_session: u64,
+    pub foreign_data: u64,
+    pub bit_corruption: u64,
+}
+
+impl FailureBreakdown {
+    pub fn record(&mut self, kind: FailureKind) {
+        *self.count_mut(kind) += 1;
+    }
+
+    pub fn count(&self, kind: FailureKind) -> u64 {
+        match kind {
+            FailureKind::ZeroFill => self.zero_fill,
+            FailureKind::OnesFill => self.ones_fill,
+            FailureKind::RepeatedBlock => self.repeated_block,
+            FailureKind::MisplacedData => self.misplaced_data,
+            FailureKind::StaleSession => self.stale_session,
+            FailureKind::ForeignData => self.foreign_data,
+            FailureKind::BitCorruption => self.bit_corruption,
+        }
+    }
+
+    pub fn merge(&mut self, other: &FailureBreakdown) {
+        self.zero_fill += other.zero_fill;
+        self.ones_fill += other.ones_fill;
+        self.repeated_block += other.repeated_block;
+        self.misplaced_data += other.misplaced_data;
+        self.stale_session += other.stale_session;
+        self.foreign_data += other.foreign_data;
+        self.bit_corruption += other.bit_corruption;
+    }
+
+    fn count_mut(&mut self, kind: FailureKind) -> &mut u64 {
+        match kind {
+            FailureKind::ZeroFill => &mut self.zero_fill,
+            FailureKind::OnesFill => &mut self.ones_fill,
+            FailureKind::RepeatedBlock => &mut self.repeated_block,
+            FailureKind::MisplacedData => &mut self.misplaced_data,
+            FailureKind::StaleSession => &mut self.stale_session,
+            FailureKind::ForeignData => &mut self.foreign_data,
+            FailureKind::BitCorruption => &mut self.bit_corruption,
+        }
+    }
+}
+
+// Remembers the content of recent bad blocks so a drive that hands back the
+// same block for many offsets is told apart from one returning old files.
+#[derive(Debug, Clone, Default)]
+pub struct BlockFingerprints {
+    seen: Vec<(u64, u64)>,
+}
+
+impl BlockFingerprints {
+    fn repeats(&mut self, offset: u64, data: &[u8]) -> bool {
+        let fingerprint = fingerprint(data);
+        let repeated = self
+            .seen
+            .iter()
+            .any(|&(seen, seen_offset)| seen == fingerprint && seen_offset != offset);
+        if !repeated && self.seen.len() < MAX_FINGERPRINTS {
+            self.seen.push((fingerprint, offset));
+        }
+        repeated
+    }
+
+    pub fn entries(&self) -> &[(u64, u64)] {
+        &self.seen
+    }
+
+    // Puts back an entry saved by a checkpoint.
+    pub fn restore(&mut self, fingerprint: u64, offset: u64) {
+        if self.seen.len() < MAX_FINGERPRINTS {
+            self.seen.push((fingerprint, offset));
+        }
+    }
+
+    pub fn merge(&mut self, other: &BlockFingerprints) {
+        let room = MAX_FINGERPRINTS.saturating_sub(self.seen.len());
+        self.seen.extend(other.seen.iter().take(room));
+    }
+}
+
+fn fingerprint(data: &[u8]) -> u64 {
+    let mut hash = 0xCBF2_9CE4_8422_2325u64 ^ data.len() as u64;
+    let mut words = data.chunks_exact(8);
+    for word in &mut words {
+        let word = u64::from_le_bytes(word.try_into().unwrap_or_default());
+        hash = (hash ^ word)
+            .wrapping_mul(0x0000_0100_0000_01B3)
+            .rotate_left(29);
+    }
+    for &byte in words.remainder() {
+        hash = (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01B3);
+    }
+    hash
+}
+
+fn is_repeating(data: &[u8]) -> bool {
+    if let Some(&first) = data.first()
+        && data.iter().all(|&byte| byte == first)
+    {
+        return true;
+    }
+    if data.len() < 2 * SECTOR_SIZE || !data.len().is_multiple_of(SECTOR_SIZE) {
+        return false;
+    }
+    let (first, rest) = data.split_at(SECTOR_SIZE);
+    rest.chunks_exact(SECTOR_SIZE).all(|sector| sector == first)
+}
+
+fn has_stale_header(session_seed: u64, offset: u64, actual: &[u8]) -> bool {
+    let mut index = first_sector_index(offset);
+    while index < actual.len() {
+        if let Some(header) = decode_sector_header(&actual[index..])
+            && header.session_seed != session_seed
+        {
+            return true;
+        }
+        index += SECTOR_SIZE;
+    }
+    false
+}
+
+// Definite signatures come first: uniform fill, data from another offset of
+// this session, or headers left by an earlier run. A block that still mostly
+// matches is bit corruption; otherwise it is either a copy of something the
+// drive already returned or data that was never written by this test.
+pub fn classify_failure(
+    pattern: Pattern,
+    offset: u64,
+    expected: &[u8],
+    actual: &[u8],
+    misplaced: bool,
+    fingerprints: &mut BlockFingerprints,
+) -> Option<FailureKind> {
+    if expected.is_empty() || expected.len() != actual.len() || expected == actual {
+        return None;
+    }
+    if actual.iter().all(|&byte| byte == 0x00) {
+        return Some(FailureKind::ZeroFill);
+    }
+    if actual.iter().all(|&byte| byte == 0xFF) {
+        return Some(FailureKind::OnesFill);
+    }
+    if misplaced {
+        return Some(FailureKind::MisplacedData);
+    }
+    if has_stale_header(pattern.session_seed, offset, actual) {
+        return Some(FailureKind::StaleSession);
+    }
+
+    let differing = expected
+        .iter()
+        .zip(actual)
+        .filter(|(want, got)| want != got)
+        .count();
+    if differing * 2 <= actual.len() {
+        return Some(FailureKind::BitCorruption);
+    }
+    if fingerprints.repeats(offset, actual) || is_repeating(actual) {
+        return Some(FailureKind::RepeatedBlock);
+    }
+    Some(FailureKind::ForeignData)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BlockFingerprints, FailureBreakdown, FailureKind, classify_failure};
+    use crate::core_logic::{
+        DriveHealthStatus, Pattern, PatternLayout, SECTOR_SIZE, SPLITMIX, fill_block,
+    };
+
+    const SESSION: Pattern = Pattern {
+        session_seed: 0x5EED,
+        layout: PatternLayout::SectorHeaders,
+        generator: &SPLITMIX,
+    };
+
+    fn expected(offset: u64, len: usize) -> Vec<u8> {
+        let mut block = vec![0u8; len];
+        fill_block(SESSION, offset, &mut block);
+        block
+    }
+
+    fn classify(offset: u64, actual: &[u8], fingerprints: &mut BlockFingerprints) -> FailureKind {
+        let expected = expected(offset, actual.len());
+        classify_failure(SESSION, offset, &expected, actual, false, fingerprints).unwrap()
+    }
+
+    #[test]
+    fn test_classifies_uniform_fills() {
+        let mut fingerprints = BlockFingerprints::default();
+        let len = 2 * SECTOR_SIZE;
+        assert_eq!(
+            classify(0, &vec![0x00; len], &mut fingerprints),
+            FailureKind::ZeroFill
+        );
+        assert_eq!(
+            classify(0, &vec![0xFF; len], &mut fingerprints),
+            FailureKind::OnesFill
+        );
+        assert_eq!(
+            classify(0, &vec![0x5A; len], &mut fingerprints),
+            FailureKind::RepeatedBlock
+        );
+    }
+
+    #[test]
+    fn test_classifies_bit_corruption_and_match() {
+        let mut fingerprints = BlockFingerprints::default();
+        let mut actual = expected(0, 2 * SECTOR_SIZE);
+        let expected = actual.clone();
+        assert_eq!(
+            classify_failure(SESSION, 0, &expected, &actual, false, &mut fingerprints),
+            None
+        );
+        actual[100] ^= 0x01;
+        assert_eq!(
+            classify(0, &actual, &mut fingerprints),
+            FailureKind::BitCorruption
+        );
+    }
+
+    #[test]
+    fn test_classifies_stale_session_headers() {
+        let mut fingerprints = BlockFingerprints::default();
+        let stale = Pattern {
+            session_seed: 0xDEAD,
+            ..SESSION
+        };
+        let mut actual = vec![0u8; 2 * SECTOR_SIZE];
+        fill_block(stale, 0, &mut actual);
+        assert_eq!(
+            classify(0, &actual, &mut fingerprints),
+            FailureKind::StaleSession
+        );
+    }
+
+    #[test]
+    fn test_tells_repeated_blocks_from_foreign_data() {
+        let mut fingerprints = BlockFingerprints::default();
+        let foreign: Vec<u8> = (0..2 * SECTOR_SIZE)
+            .map(|index| (index * 31 % 251) as u8)
+            .collect();
+        assert_eq!(
+            classify(0, &foreign, &mut fingerprints),
+            FailureKind::ForeignData
+        );
+        assert_eq!(
+            classify(0, &foreign, &mut fingerprints),
+            FailureKind::ForeignData
+        );
+        assert_eq!(
+            classify(2 * SECTOR_SIZE as u64, &foreign, &mut fingerprints),
+            FailureKind::RepeatedBlock
+        );
+    }
+
+    #[test]
+    fn test_failure_kinds_map_to_status() {
+        let mut breakdown = FailureBreakdown::default();
+        breakdown.record(FailureKind::ForeignData);
+        breakdown.record(FailureKind::ForeignData);
+        assert_eq!(breakdown.count(FailureKind::ForeignData), 2);
+        assert_eq!(
+            FailureKind::StaleSession.status(),
+            DriveHealthStatus::StaleData
+        );
+        assert_eq!(
+            FailureKind::OnesFill.status(),
+            DriveHealthStatus::FakeCapacity
+        );
+    }
+}
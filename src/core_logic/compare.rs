@@ -0,0 +1,117 @@
+use std::ops::Range;
+
+pub(super) fn first_difference(expected: &[u8], actual: &[u8]) -> Option<usize> {
+    let len = expected.len().min(actual.len());
+    let (expected, actual) = (&expected[..len], &actual[..len]);
+
+    #[cfg(target_arch = "x86_64")]
+    let start = {
+        // SSE2 is part of the x86_64 baseline, so no runtime detection is needed.
+        match unsafe { sse2_first_difference(expected, actual) } {
+            Ok(index) => return Some(index),
+            Err(compared) => compared,
+        }
+    };
+    #[cfg(not(target_arch = "x86_64"))]
+    let start = 0;
+
+    words_first_difference(&expected[start..], &actual[start..]).map(|index| start + index)
+}
+
+pub(super) fn difference_ranges(
+    expected: &[u8],
+    actual: &[u8],
+    base: usize,
+    ranges: &mut Vec<Range<usize>>,
+) {
+    let len = expected.len().min(actual.len());
+    let mut index = 0;
+    while let Some(found) = first_difference(&expected[index..len], &actual[index..len]) {
+        let start = index + found;
+        let end = expected[start..len]
+            .iter()
+            .zip(&actual[start..len])
+            .position(|(left, right)| left == right)
+            .map_or(len, |run| start + run);
+        match ranges.last_mut() {
+            Some(last) if last.end == base + start => last.end = base + end,
+            _ => ranges.push(base + start..base + end),
+        }
+        index = end;
+    }
+}
+
+fn words_first_difference(expected: &[u8], actual: &[u8]) -> Option<usize> {
+    let mut expected_words = expected.chunks_exact(8);
+    let mut actual_words = actual.chunks_exact(8);
+    for (word_index, (left, right)) in (&mut expected_words).zip(&mut actual_words).enumerate() {
+        let left = u64::from_le_bytes(left.try_into().unwrap_or_default());
+        let right = u64::from_le_bytes(right.try_into().unwrap_or_default());
+        let diff = left ^ right;
+        if diff != 0 {
+            return Some(word_index * 8 + (diff.trailing_zeros() / 8) as usize);
+        }
+    }
+
+    let base = expected.len() - expected_words.remainder().len();
+    expected_words
+        .remainder()
+        .iter()
+        .zip(actual_words.remainder())
+        .position(|(left, right)| left != right)
+        .map(|index| base + index)
+}
+
+// Compares 16-byte lanes and returns either the first differing index or the
+// number of bytes covered, leaving the tail to the word-wide fallback.
+#[cfg(target_arch = "x86_64")]
+unsafe fn sse2_first_difference(expected: &[u8], actual: &[u8]) -> Result<usize, usize> {
+    use std::arch::x86_64::{__m128i, _mm_cmpeq_epi8, _mm_loadu_si128, _mm_movemask_epi8};
+
+    const LANE: usize = 16;
+    let lanes = expected.len() / LANE;
+    for lane in 0..lanes {
+        let index = lane * LANE;
+        let mask = unsafe {
+            let left = _mm_loadu_si128(expected.as_ptr().add(index) as *const __m128i);
+            let right = _mm_loadu_si128(actual.as_ptr().add(index) as *const __m128i);
+            _mm_movemask_epi8(_mm_cmpeq_epi8(left, right)) as u32
+        };
+        if mask != 0xFFFF {
+            return Ok(index + (!mask).trailing_zeros() as usize);
+        }
+    }
+    Err(lanes * LANE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{difference_ranges, first_difference, words_first_difference};
+
+    #[test]
+    fn test_first_difference_matches_bytewise_scan() {
+        let expected: Vec<u8> = (0..203u32).map(|value| (value * 7) as u8).collect();
+        assert_eq!(first_difference(&expected, &expected), None);
+
+        for flip in [0, 7, 8, 15, 16, 100, 191, 192, 202] {
+            let mut actual = expected.clone();
+            actual[flip] ^= 0x10;
+            assert_eq!(first_difference(&expected, &actual), Some(flip));
+            assert_eq!(words_first_difference(&expected, &actual), Some(flip));
+        }
+    }
+
+    #[test]
+    fn test_difference_ranges_merge_across_calls() {
+        let expected = [0u8; 32];
+        let mut actual = [0u8; 32];
+        actual[3..6].fill(1);
+        actual[14..16].fill(1);
+        actual[16..20].fill(1);
+
+        let mut ranges = Vec::new();
+        difference_ranges(&expected[..16], &actual[..16], 0, &mut ranges);
+        difference_ranges(&expected[16..], &actual[16..], 16, &mut ranges);
+        assert_eq!(ranges, vec![3..6, 14..20]);
+    }
+}
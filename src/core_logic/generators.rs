@@ -0,0 +1,376 @@
+use std::fmt;
+use std::num::Wrapping;
+
+pub trait PatternStream {
+    fn next_u64(&mut self) -> u64;
+
+    // Bytes are the little-endian encoding of consecutive words. Callers that
+    // split one block across several calls must pass multiples of 8 bytes to
+    // every call but the last to stay in step with the stream.
+    fn fill_bytes(&mut self, buffer: &mut [u8]) {
+        fill_words(self, 0, buffer);
+    }
+}
+
+fn fill_words<S: PatternStream + ?Sized>(stream: &mut S, invert: u64, buffer: &mut [u8]) {
+    let mut chunks = buffer.chunks_exact_mut(8);
+    for chunk in &mut chunks {
+        chunk.copy_from_slice(&(stream.next_u64() ^ invert).to_le_bytes());
+    }
+    let tail = chunks.into_remainder();
+    if !tail.is_empty() {
+        let word = (stream.next_u64() ^ invert).to_le_bytes();
+        tail.copy_from_slice(&word[..tail.len()]);
+    }
+}
+
+pub trait PatternGenerator: fmt::Debug + Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn stream(&self, session_seed: u64, offset: u64) -> BlockStream;
+}
+
+// The stream of one block, held by value. A fill picks the concrete generator
+// once and then runs a loop specialised for it, so the hot path neither
+// allocates nor makes an indirect call per word. Inverting is a mask applied
+// to every word.
+pub struct BlockStream {
+    source: StreamSource,
+    invert: u64,
+}
+
+enum StreamSource {
+    SplitMix(SplitMix64),
+    WyRand(WyRand),
+    Fixed(FixedStream),
+    ChaCha20(ChaCha20Stream),
+}
+
+impl BlockStream {
+    fn new(source: StreamSource) -> Self {
+        Self { source, invert: 0 }
+    }
+
+    fn inverted(self) -> Self {
+        Self {
+            invert: !self.invert,
+            ..self
+        }
+    }
+}
+
+impl PatternStream for BlockStream {
+    #[inline(always)]
+    fn next_u64(&mut self) -> u64 {
+        let word = match &mut self.source {
+            StreamSource::SplitMix(stream) => stream.next_u64(),
+            StreamSource::WyRand(stream) => stream.next_u64(),
+            StreamSource::Fixed(stream) => stream.next_u64(),
+            StreamSource::ChaCha20(stream) => stream.next_u64(),
+        };
+        word ^ self.invert
+    }
+
+    fn fill_bytes(&mut self, buffer: &mut [u8]) {
+        let invert = self.invert;
+        match &mut self.source {
+            StreamSource::SplitMix(stream) => fill_words(stream, invert, buffer),
+            StreamSource::WyRand(stream) => fill_words(stream, invert, buffer),
+            StreamSource::Fixed(stream) => fill_words(stream, invert, buffer),
+            StreamSource::ChaCha20(stream) => fill_words(stream, invert, buffer),
+        }
+    }
+}
+
+pub static SPLITMIX: SplitMixGenerator = SplitMixGenerator;
+pub static WYRAND: WyRandGenerator = WyRandGenerator;
+pub static ZEROS: FixedGenerator = FixedGenerator::new("zeros", 0x0000_0000_0000_0000);
+pub static ONES: FixedGenerator = FixedGenerator::new("ones", 0xFFFF_FFFF_FFFF_FFFF);
+pub static ALTERNATING: FixedGenerator = FixedGenerator::new("alternating", 0xAA55_AA55_AA55_AA55);
+pub static CHACHA20: ChaCha20Generator = ChaCha20Generator;
+pub static SPLITMIX_INVERTED: InvertedGenerator =
+    InvertedGenerator::new("splitmix_inverted", &SPLITMIX);
+pub static WYRAND_INVERTED: InvertedGenerator = InvertedGenerator::new("wyrand_inverted", &WYRAND);
+pub static ALTERNATING_INVERTED: InvertedGenerator =
+    InvertedGenerator::new("alternating_inverted", &ALTERNATING);
+pub static CHACHA20_INVERTED: InvertedGenerator =
+    InvertedGenerator::new("chacha20_inverted", &CHACHA20);
+
+pub static BUILTIN_GENERATORS: [&dyn PatternGenerator; 10] = [
+    &SPLITMIX,
+    &WYRAND,
+    &ZEROS,
+    &ONES,
+    &ALTERNATING,
+    &CHACHA20,
+    &SPLITMIX_INVERTED,
+    &WYRAND_INVERTED,
+    &ALTERNATING_INVERTED,
+    &CHACHA20_INVERTED,
+];
+
+pub fn builtin_generator(name: &str) -> Option<&'static dyn PatternGenerator> {
+    BUILTIN_GENERATORS
+        .iter()
+        .copied()
+        .find(|generator| generator.name() == name)
+}
+
+// The builtin whose output is the bitwise complement of `generator` for the
+// same seed, so two passes together drive every bit both ways.
+pub fn inverse_generator(
+    generator: &dyn PatternGenerator,
+) -> Option<&'static dyn PatternGenerator> {
+    let inverse = match generator.name() {
+        "zeros" => "ones".to_string(),
+        "ones" => "zeros".to_string(),
+        name => match name.strip_suffix("_inverted") {
+            Some(base) => base.to_string(),
+            None => format!("{name}_inverted"),
+        },
+    };
+    builtin_generator(&inverse)
+}
+
+pub(super) fn generate_seed(session_seed: u64, offset: u64) -> u64 {
+    let mut z = Wrapping(offset ^ session_seed);
+    z += Wrapping(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)) * Wrapping(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)) * Wrapping(0x94D049BB133111EB);
+    (z ^ (z >> 31)).0
+}
+
+#[derive(Debug)]
+pub struct SplitMixGenerator;
+
+impl PatternGenerator for SplitMixGenerator {
+    fn name(&self) -> &'static str {
+        "splitmix"
+    }
+
+    fn stream(&self, session_seed: u64, offset: u64) -> BlockStream {
+        BlockStream::new(StreamSource::SplitMix(SplitMix64::new(generate_seed(
+            session_seed,
+            offset,
+        ))))
+    }
+}
+
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+}
+
+impl PatternStream for SplitMix64 {
+    #[inline(always)]
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+}
+
+#[derive(Debug)]
+pub struct WyRandGenerator;
+
+impl PatternGenerator for WyRandGenerator {
+    fn name(&self) -> &'static str {
+        "wyrand"
+    }
+
+    fn stream(&self, session_seed: u64, offset: u64) -> BlockStream {
+        BlockStream::new(StreamSource::WyRand(WyRand {
+            state: generate_seed(session_seed, offset),
+        }))
+    }
+}
+
+struct WyRand {
+    state: u64,
+}
+
+impl PatternStream for WyRand {
+    #[inline(always)]
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0xA076_1D64_78BD_642F);
+        let product = (self.state as u128) * ((self.state ^ 0xE703_7ED1_A0B4_28DB) as u128);
+        ((product >> 64) ^ product) as u64
+    }
+}
+
+#[derive(Debug)]
+pub struct FixedGenerator {
+    name: &'static str,
+    word: u64,
+}
+
+impl FixedGenerator {
+    pub const fn new(name: &'static str, word: u64) -> Self {
+        Self { name, word }
+    }
+}
+
+impl PatternGenerator for FixedGenerator {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn stream(&self, _session_seed: u64, _offset: u64) -> BlockStream {
+        BlockStream::new(StreamSource::Fixed(FixedStream { word: self.word }))
+    }
+}
+
+struct FixedStream {
+    word: u64,
+}
+
+impl PatternStream for FixedStream {
+    #[inline(always)]
+    fn next_u64(&mut self) -> u64 {
+        self.word
+    }
+}
+
+#[derive(Debug)]
+pub struct InvertedGenerator {
+    name: &'static str,
+    base: &'static dyn PatternGenerator,
+}
+
+impl InvertedGenerator {
+    pub const fn new(name: &'static str, base: &'static dyn PatternGenerator) -> Self {
+        Self { name, base }
+    }
+}
+
+impl PatternGenerator for InvertedGenerator {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn stream(&self, session_seed: u64, offset: u64) -> BlockStream {
+        self.base.stream(session_seed, offset).inverted()
+    }
+}
+
+// The key is expanded from the session seed and the block offset is the nonce,
+// so every block gets an independent keystream that cannot be predicted
+// without the seed.
+#[derive(Debug)]
+pub struct ChaCha20Generator;
+
+impl PatternGenerator for ChaCha20Generator {
+    fn name(&self) -> &'static str {
+        "chacha20"
+    }
+
+    fn stream(&self, session_seed: u64, offset: u64) -> BlockStream {
+        let mut expander = SplitMix64::new(session_seed);
+        let mut key = [0u32; 8];
+        for pair in key.chunks_exact_mut(2) {
+            let word = expander.next_u64();
+            pair[0] = word as u32;
+            pair[1] = (word >> 32) as u32;
+        }
+        BlockStream::new(StreamSource::ChaCha20(ChaCha20Stream {
+            key,
+            nonce: [0, offset as u32, (offset >> 32) as u32],
+            counter: 0,
+            block: [0; 16],
+            index: 16,
+        }))
+    }
+}
+
+struct ChaCha20Stream {
+    key: [u32; 8],
+    nonce: [u32; 3],
+    counter: u32,
+    block: [u32; 16],
+    index: usize,
+}
+
+impl ChaCha20Stream {
+    const CONSTANTS: [u32; 4] = [0x6170_7865, 0x3320_646E, 0x7962_2D32, 0x6B20_6574];
+
+    fn refill(&mut self) {
+        let mut input = [0u32; 16];
+        input[0..4].copy_from_slice(&Self::CONSTANTS);
+        input[4..12].copy_from_slice(&self.key);
+        input[12] = self.counter;
+        input[13..16].copy_from_slice(&self.nonce);
+
+        let mut state = input;
+        for _ in 0..10 {
+            quarter_round(&mut state, 0, 4, 8, 12);
+            quarter_round(&mut state, 1, 5, 9, 13);
+            quarter_round(&mut state, 2, 6, 10, 14);
+            quarter_round(&mut state, 3, 7, 11, 15);
+            quarter_round(&mut state, 0, 5, 10, 15);
+            quarter_round(&mut state, 1, 6, 11, 12);
+            quarter_round(&mut state, 2, 7, 8, 13);
+            quarter_round(&mut state, 3, 4, 9, 14);
+        }
+        for (word, initial) in state.iter_mut().zip(input) {
+            *word = word.wrapping_add(initial);
+        }
+
+        self.block = state;
+        self.index = 0;
+        self.counter = self.counter.wrapping_add(1);
+    }
+}
+
+impl PatternStream for ChaCha20Stream {
+    #[inline(always)]
+    fn next_u64(&mut self) -> u64 {
+        if self.index >= self.block.len() {
+            self.refill();
+        }
+        let low = self.block[self.index] as u64;
+        let high = self.block[self.index + 1] as u64;
+        self.index += 2;
+        low | (high << 32)
+    }
+}
+
+#[inline(always)]
+fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChaCha20Stream, PatternStream};
+
+    #[test]
+    fn test_chacha20_block_matches_rfc7539() {
+        let key: [u32; 8] = std::array::from_fn(|i| {
+            let base = (i * 4) as u32;
+            base | (base + 1) << 8 | (base + 2) << 16 | (base + 3) << 24
+        });
+        let mut stream = ChaCha20Stream {
+            key,
+            nonce: [0x0900_0000, 0x4A00_0000, 0],
+            counter: 1,
+            block: [0; 16],
+            index: 16,
+        };
+        assert_eq!(stream.next_u64(), 0x1559_3BD1_E4E7_F110);
+        assert_eq!(stream.next_u64(), 0xC471_20A3_1FDD_0F50);
+    }
+}
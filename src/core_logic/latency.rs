@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use serde::Serialize;
+
+const LATENCY_BUCKETS_US: [u64; 5] = [1_000, 10_000, 100_000, 1_000_000, 10_000_000];
+const MAX_SLOWEST: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SlowIo {
+    pub offset: u64,
+    pub latency_us: u64,
+}
+
+// `histogram` buckets requests by latency: under 1 ms, 1-10 ms, 10-100 ms,
+// 100 ms-1 s, 1-10 s and 10 s or more. `slowest` keeps the worst requests,
+// slowest first. A request counts as a stall when it takes longer than
+// `stall_threshold_us`; a threshold of zero turns stall counting off.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LatencyStats {
+    pub count: u64,
+    pub total_us: u64,
+    pub max_us: u64,
+    pub histogram: [u64; 6],
+    pub slowest: Vec<SlowIo>,
+    pub stall_threshold_us: u64,
+    pub stalls: u64,
+}
+
+impl LatencyStats {
+    pub fn with_stall_threshold(threshold: Duration) -> Self {
+        Self {
+            stall_threshold_us: threshold.as_micros() as u64,
+            ..Self::default()
+        }
+    }
+
+    pub fn record(&mut self, offset: u64, latency: Duration) {
+        let latency_us = latency.as_micros() as u64;
+        self.count += 1;
+        self.total_us += latency_us;
+        self.max_us = self.max_us.max(latency_us);
+        let bucket = LATENCY_BUCKETS_US
+            .iter()
+            .position(|&limit| latency_us < limit)
+            .unwrap_or(LATENCY_BUCKETS_US.len());
+        self.histogram[bucket] += 1;
+        if self.is_stall(latency) {
+            self.stalls += 1;
+        }
+        self.keep_if_slow(SlowIo { offset, latency_us });
+    }
+
+    pub fn is_stall(&self, latency: Duration) -> bool {
+        self.stall_threshold_us > 0 && latency.as_micros() as u64 > self.stall_threshold_us
+    }
+
+    pub fn mean_us(&self) -> Option<u64> {
+        (self.count > 0).then(|| self.total_us / self.count)
+    }
+
+    pub fn merge(&mut self, other: &LatencyStats) {
+        self.count += other.count;
+        self.total_us += other.total_us;
+        self.max_us = self.max_us.max(other.max_us);
+        for (bucket, count) in self.histogram.iter_mut().zip(other.histogram) {
+            *bucket += count;
+        }
+        self.stall_threshold_us = self.stall_threshold_us.max(other.stall_threshold_us);
+        self.stalls += other.stalls;
+        for &io in &other.slowest {
+            self.keep_if_slow(io);
+        }
+    }
+
+    fn keep_if_slow(&mut self, io: SlowIo) {
+        if self.slowest.len() == MAX_SLOWEST
+            && self
+                .slowest
+                .last()
+                .is_some_and(|fastest| fastest.latency_us >= io.latency_us)
+        {
+            return;
+        }
+        let index = self
+            .slowest
+            .partition_point(|kept| kept.latency_us >= io.latency_us);
+        self.slowest.insert(index, io);
+        self.slowest.truncate(MAX_SLOWEST);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::LatencyStats;
+
+    #[test]
+    fn test_keeps_slowest_requests_and_counts_stalls() {
+        let mut stats = LatencyStats::with_stall_threshold(Duration::from_secs(1));
+        for index in 0..20u64 {
+            stats.record(index * 4096, Duration::from_millis(index * 100));
+        }
+
+        assert_eq!(stats.count, 20);
+        assert_eq!(stats.max_us, 1_900_000);
+        assert_eq!(stats.histogram, [1, 0, 0, 9, 10, 0]);
+        assert_eq!(stats.stalls, 9);
+        assert_eq!(stats.slowest.len(), 8);
+        assert_eq!(stats.slowest[0].offset, 19 * 4096);
+        assert_eq!(stats.slowest[7].offset, 12 * 4096);
+
+        let mut merged = LatencyStats::default();
+        merged.merge(&stats);
+        merged.merge(&stats);
+        assert_eq!(merged.count, 40);
+        assert_eq!(merged.stalls, 18);
+        assert_eq!(merged.slowest[1].offset, 19 * 4096);
+    }
+}
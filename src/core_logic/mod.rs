@@ -0,0 +1,1301 @@
+mod bad_blocks;
+mod bit_errors;
+mod classify;
+mod compare;
+mod generators;
+mod latency;
+mod throughput;
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::ops::Range;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Serialize, Serializer};
+
+pub use bad_blocks::{BadRange, BadRangeKind, badblocks_list};
+pub use bit_errors::{BitErrorStats, BitPositionErrors, StuckBit};
+pub use classify::{BlockFingerprints, FailureBreakdown, FailureKind, classify_failure};
+pub use generators::{
+    ALTERNATING, ALTERNATING_INVERTED, BUILTIN_GENERATORS, BlockStream, CHACHA20,
+    CHACHA20_INVERTED, ChaCha20Generator, FixedGenerator, InvertedGenerator, ONES,
+    PatternGenerator, PatternStream, SPLITMIX, SPLITMIX_INVERTED, SplitMixGenerator, WYRAND,
+    WYRAND_INVERTED, WyRandGenerator, ZEROS, builtin_generator, inverse_generator,
+};
+pub use latency::{LatencyStats, SlowIo};
+pub use throughput::{
+    CacheEstimateSkip, ThroughputCurve, ThroughputPoint, WriteCacheEstimate, estimate_write_cache,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum DriveHealthStatus {
+    Healthy,
+    FakeCapacity,
+    PhysicalCorruption,
+    DataLoss,
+    StaleData,
+    Degraded,
+    ReadOnly,
+    Disconnected,
+    Unstable,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PatternLayout {
+    #[default]
+    Plain,
+    SectorHeaders,
+}
+
+impl PatternLayout {
+    pub fn name(self) -> &'static str {
+        match self {
+            PatternLayout::Plain => "plain",
+            PatternLayout::SectorHeaders => "sector_headers",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "plain" => Some(PatternLayout::Plain),
+            "sector_headers" => Some(PatternLayout::SectorHeaders),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Pattern {
+    pub session_seed: u64,
+    pub layout: PatternLayout,
+    pub generator: &'static dyn PatternGenerator,
+}
+
+impl Pattern {
+    pub fn new(
+        session_seed: u64,
+        layout: PatternLayout,
+        generator: &'static dyn PatternGenerator,
+    ) -> Self {
+        Self {
+            session_seed,
+            layout,
+            generator,
+        }
+    }
+}
+
+// What the target reported about its sector sizes and direct I/O constraints,
+// and the alignment the run settled on. Fields the platform could not query
+// are left empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DriveGeometry {
+    pub logical_sector_size: Option<u32>,
+    pub physical_sector_size: Option<u32>,
+    pub dio_mem_align: Option<u32>,
+    pub dio_offset_align: Option<u32>,
+    pub fs_block_size: Option<u64>,
+    pub alignment: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct MisplacedSector {
+    pub read_offset: u64,
+    pub written_offset: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SectorHeader {
+    pub session_seed: u64,
+    pub offset: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ScanStats {
+    pub valid_bytes: u64,
+    pub corrupted_bytes: u64,
+    pub mismatch_blocks: u64,
+    pub read_error_blocks: u64,
+    pub write_error_blocks: u64,
+    pub sample_status: Option<DriveHealthStatus>,
+    pub misplaced_sectors: Vec<MisplacedSector>,
+    pub highest_valid_end: u64,
+    pub bit_errors: BitErrorStats,
+    pub failures: FailureBreakdown,
+    pub fingerprints: BlockFingerprints,
+    pub unstable_blocks: u64,
+    pub recovered_reads: u64,
+    pub write_speed_mbps: Option<f64>,
+    pub verify_speed_mbps: Option<f64>,
+    pub write_latency: LatencyStats,
+    pub read_latency: LatencyStats,
+    pub write_throughput: ThroughputCurve,
+    pub read_throughput: ThroughputCurve,
+    pub write_cache_skip: Option<CacheEstimateSkip>,
+    pub bad_ranges: Vec<BadRange>,
+    pub geometry: Option<DriveGeometry>,
+    pub buffered_io: bool,
+}
+
+impl ScanStats {
+    pub fn record_valid(&mut self, offset: u64, len: u64) {
+        self.valid_bytes += len;
+        self.highest_valid_end = self.highest_valid_end.max(offset + len);
+    }
+
+    pub fn record_mismatch(&mut self, offset: u64, len: u64, extents: &[Range<usize>]) {
+        let corrupted: u64 = extents.iter().map(|extent| extent.len() as u64).sum();
+        for extent in extents {
+            bad_blocks::push_bad_range(
+                &mut self.bad_ranges,
+                offset + extent.start as u64,
+                offset + extent.end as u64,
+                BadRangeKind::Mismatch,
+            );
+        }
+        self.mismatch_blocks += 1;
+        self.corrupted_bytes += corrupted;
+        self.valid_bytes += len.saturating_sub(corrupted);
+    }
+
+    pub fn record_read_error(&mut self, offset: u64, len: u64) {
+        self.read_error_blocks += 1;
+        bad_blocks::push_bad_range(
+            &mut self.bad_ranges,
+            offset,
+            offset + len,
+            BadRangeKind::ReadError,
+        );
+    }
+
+    pub fn record_write_error(&mut self, offset: u64, len: u64) {
+        self.write_error_blocks += 1;
+        bad_blocks::push_bad_range(
+            &mut self.bad_ranges,
+            offset,
+            offset + len,
+            BadRangeKind::WriteError,
+        );
+    }
+
+    pub fn can_sample_misplaced(&self) -> bool {
+        self.misplaced_sectors.len() < MAX_MISPLACED_SAMPLES
+    }
+
+    pub fn record_misplaced(&mut self, sector: MisplacedSector) {
+        if self.can_sample_misplaced() {
+            self.misplaced_sectors.push(sector);
+        }
+    }
+
+    pub fn record_failure(&mut self, kind: FailureKind) {
+        self.failures.record(kind);
+        self.record_status(kind.status());
+    }
+
+    pub fn record_status(&mut self, status: DriveHealthStatus) {
+        if self
+            .sample_status
+            .is_none_or(|existing| status_severity(status) > status_severity(existing))
+        {
+            self.sample_status = Some(status);
+        }
+    }
+
+    // Folds in the stats of the region that follows this one. Speeds, write
+    // latency and throughput curves are left alone since they describe the
+    // whole phase.
+    pub fn merge(&mut self, other: &ScanStats) {
+        self.valid_bytes += other.valid_bytes;
+        self.corrupted_bytes += other.corrupted_bytes;
+        self.mismatch_blocks += other.mismatch_blocks;
+        self.read_error_blocks += other.read_error_blocks;
+        self.write_error_blocks += other.write_error_blocks;
+        if let Some(status) = other.sample_status {
+            self.record_status(status);
+        }
+        for &sector in &other.misplaced_sectors {
+            self.record_misplaced(sector);
+        }
+        self.highest_valid_end = self.highest_valid_end.max(other.highest_valid_end);
+        self.bit_errors.merge(&other.bit_errors);
+        self.failures.merge(&other.failures);
+        self.fingerprints.merge(&other.fingerprints);
+        self.unstable_blocks += other.unstable_blocks;
+        self.recovered_reads += other.recovered_reads;
+        self.read_latency.merge(&other.read_latency);
+        self.buffered_io |= other.buffered_io;
+        for range in &other.bad_ranges {
+            bad_blocks::push_bad_range(&mut self.bad_ranges, range.start, range.end, range.kind);
+        }
+    }
+}
+
+pub const SECTOR_SIZE: usize = 4096;
+const SECTOR_MAGIC: [u8; 8] = *b"TBYTSECT";
+const SECTOR_HEADER_LEN: usize = 28;
+const MAX_MISPLACED_SAMPLES: usize = 16;
+const GIB: u64 = 1024 * 1024 * 1024;
+const DEGRADED_SPEED_MBPS: f64 = 2.0;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosisReport {
+    pub total_capacity: u64,
+    pub tested_bytes: u64,
+    pub valid_bytes: u64,
+    pub corrupted_bytes: u64,
+    pub error_count: u64,
+    pub write_error_blocks: u64,
+    pub health_score: f64,
+    pub status: DriveHealthStatus,
+    pub conclusion: String,
+    #[serde(serialize_with = "serialize_session_seed")]
+    pub session_seed: u64,
+    pub pattern_generator: &'static str,
+    pub misplaced_sectors: Vec<MisplacedSector>,
+    pub usable_capacity: Option<u64>,
+    pub wrap_modulus: Option<u64>,
+    pub bit_errors: BitErrorStats,
+    pub failures: FailureBreakdown,
+    pub unstable_blocks: u64,
+    pub recovered_reads: u64,
+    pub write_speed_mbps: Option<f64>,
+    pub verify_speed_mbps: Option<f64>,
+    pub write_latency: LatencyStats,
+    pub read_latency: LatencyStats,
+    pub write_throughput: ThroughputCurve,
+    pub read_throughput: ThroughputCurve,
+    pub write_cache: Option<WriteCacheEstimate>,
+    pub write_cache_skip: Option<CacheEstimateSkip>,
+    pub bad_ranges: Vec<BadRange>,
+    pub geometry: Option<DriveGeometry>,
+    pub buffered_io: bool,
+    pub passes: Vec<PassReport>,
+}
+
+// One write and verify pass of a multi-pass run.
+#[derive(Debug, Clone, Serialize)]
+pub struct PassReport {
+    pub pattern_generator: &'static str,
+    pub status: DriveHealthStatus,
+    pub tested_bytes: u64,
+    pub error_count: u64,
+    pub corrupted_bytes: u64,
+    pub write_speed_mbps: Option<f64>,
+    pub verify_speed_mbps: Option<f64>,
+}
+
+impl DiagnosisReport {
+    pub fn no_data(pattern: Pattern, conclusion: String) -> Self {
+        Self {
+            total_capacity: 0,
+            tested_bytes: 0,
+            valid_bytes: 0,
+            corrupted_bytes: 0,
+            error_count: 0,
+            write_error_blocks: 0,
+            health_score: 0.0,
+            status: DriveHealthStatus::DataLoss,
+            conclusion,
+            session_seed: pattern.session_seed,
+            pattern_generator: pattern.generator.name(),
+            misplaced_sectors: Vec::new(),
+            usable_capacity: None,
+            wrap_modulus: None,
+            bit_errors: BitErrorStats::default(),
+            failures: FailureBreakdown::default(),
+            unstable_blocks: 0,
+            recovered_reads: 0,
+            write_speed_mbps: None,
+            verify_speed_mbps: None,
+            write_latency: LatencyStats::default(),
+            read_latency: LatencyStats::default(),
+            write_throughput: ThroughputCurve::default(),
+            read_throughput: ThroughputCurve::default(),
+            write_cache: None,
+            write_cache_skip: None,
+            bad_ranges: Vec::new(),
+            geometry: None,
+            buffered_io: false,
+            passes: Vec::new(),
+        }
+    }
+
+    // For runs cut short before verification, e.g. by a write-protected or
+    // vanished device, where the status itself is the whole finding.
+    pub fn interrupted(pattern: Pattern, total_capacity: u64, status: DriveHealthStatus) -> Self {
+        let stats = ScanStats {
+            sample_status: Some(status),
+            ..ScanStats::default()
+        };
+        generate_report(total_capacity, 0, &stats, pattern)
+    }
+}
+
+// Seeds are full 64-bit values; JSON consumers would lose precision on a number.
+fn serialize_session_seed<S: Serializer>(seed: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("{seed:016x}"))
+}
+
+pub fn generate_session_seed() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    if let Ok(elapsed) = SystemTime::now().duration_since(UNIX_EPOCH) {
+        hasher.write_u128(elapsed.as_nanos());
+    }
+    hasher.write_u32(std::process::id());
+    hasher.finish()
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn encode_sector_header(session_seed: u64, offset: u64) -> [u8; SECTOR_HEADER_LEN] {
+    let mut header = [0u8; SECTOR_HEADER_LEN];
+    header[0..8].copy_from_slice(&SECTOR_MAGIC);
+    header[8..16].copy_from_slice(&session_seed.to_le_bytes());
+    header[16..24].copy_from_slice(&offset.to_le_bytes());
+    let crc = crc32(&header[0..24]);
+    header[24..28].copy_from_slice(&crc.to_le_bytes());
+    header
+}
+
+pub fn decode_sector_header(sector: &[u8]) -> Option<SectorHeader> {
+    if sector.len() < SECTOR_HEADER_LEN || sector[0..8] != SECTOR_MAGIC {
+        return None;
+    }
+    let crc = u32::from_le_bytes(sector[24..28].try_into().ok()?);
+    if crc32(&sector[0..24]) != crc {
+        return None;
+    }
+    Some(SectorHeader {
+        session_seed: u64::from_le_bytes(sector[8..16].try_into().ok()?),
+        offset: u64::from_le_bytes(sector[16..24].try_into().ok()?),
+    })
+}
+
+fn first_sector_index(offset: u64) -> usize {
+    let misalignment = (offset % SECTOR_SIZE as u64) as usize;
+    (SECTOR_SIZE - misalignment) % SECTOR_SIZE
+}
+
+fn overlay_sector_headers(session_seed: u64, offset: u64, buffer: &mut [u8]) {
+    let end = offset + buffer.len() as u64;
+    let mut base = offset - offset % SECTOR_SIZE as u64;
+    while base < end {
+        let start = base.max(offset);
+        let stop = (base + SECTOR_HEADER_LEN as u64).min(end);
+        if start < stop {
+            let header = encode_sector_header(session_seed, base);
+            buffer[(start - offset) as usize..(stop - offset) as usize]
+                .copy_from_slice(&header[(start - base) as usize..(stop - base) as usize]);
+        }
+        base += SECTOR_SIZE as u64;
+    }
+}
+
+pub fn fill_block(pattern: Pattern, offset: u64, buffer: &mut [u8]) {
+    let mut stream = pattern.generator.stream(pattern.session_seed, offset);
+    stream.fill_bytes(buffer);
+
+    if pattern.layout == PatternLayout::SectorHeaders {
+        overlay_sector_headers(pattern.session_seed, offset, buffer);
+    }
+}
+
+pub fn verify_block(pattern: Pattern, offset: u64, buffer: &[u8]) -> Result<(), usize> {
+    const CHUNK: usize = 4096;
+    let mut stream = pattern.generator.stream(pattern.session_seed, offset);
+    let mut expected = [0u8; CHUNK];
+
+    for (chunk_index, actual) in buffer.chunks(CHUNK).enumerate() {
+        let start = chunk_index * CHUNK;
+        let expected = &mut expected[..actual.len()];
+        stream.fill_bytes(expected);
+        if pattern.layout == PatternLayout::SectorHeaders {
+            overlay_sector_headers(pattern.session_seed, offset + start as u64, expected);
+        }
+        if let Some(index) = compare::first_difference(expected, actual) {
+            return Err(start + index);
+        }
+    }
+    Ok(())
+}
+
+pub fn mismatch_extents(pattern: Pattern, offset: u64, buffer: &[u8]) -> Vec<Range<usize>> {
+    const CHUNK: usize = 4096;
+    let mut stream = pattern.generator.stream(pattern.session_seed, offset);
+    let mut expected = [0u8; CHUNK];
+    let mut extents = Vec::new();
+
+    for (chunk_index, actual) in buffer.chunks(CHUNK).enumerate() {
+        let start = chunk_index * CHUNK;
+        let expected = &mut expected[..actual.len()];
+        stream.fill_bytes(expected);
+        if pattern.layout == PatternLayout::SectorHeaders {
+            overlay_sector_headers(pattern.session_seed, offset + start as u64, expected);
+        }
+        compare::difference_ranges(expected, actual, start, &mut extents);
+    }
+    extents
+}
+
+pub fn find_misplaced_sector(
+    session_seed: u64,
+    offset: u64,
+    actual: &[u8],
+) -> Option<MisplacedSector> {
+    let mut index = first_sector_index(offset);
+    while index < actual.len() {
+        let read_offset = offset + index as u64;
+        if let Some(header) = decode_sector_header(&actual[index..])
+            && header.session_seed == session_seed
+            && header.offset != read_offset
+        {
+            return Some(MisplacedSector {
+                read_offset,
+                written_offset: header.offset,
+            });
+        }
+        index += SECTOR_SIZE;
+    }
+    None
+}
+
+pub fn find_matching_offset(
+    pattern: Pattern,
+    candidates: &[u64],
+    current: u64,
+    buffer: &[u8],
+) -> Option<u64> {
+    candidates
+        .iter()
+        .copied()
+        .find(|&offset| offset != current && verify_block(pattern, offset, buffer).is_ok())
+}
+
+// Fakes usually wrap at a power of two or a whole number of GiB. A block that
+// aliases is compared against the same block one modulus away on either side,
+// and against the last write that mapped onto it during a sequential fill.
+pub fn alias_candidates(offset: u64, len: u64, block_size: u64, total_bytes: u64) -> Vec<u64> {
+    let mut candidates = Vec::new();
+    for modulus in alias_moduli(block_size, total_bytes) {
+        if offset >= modulus {
+            candidates.push(offset - modulus);
+        }
+        if offset + modulus + len <= total_bytes {
+            let last_alias = (total_bytes - len - offset) / modulus;
+            candidates.push(offset + last_alias * modulus);
+            if last_alias > 1 {
+                candidates.push(offset + modulus);
+            }
+        }
+    }
+    candidates.retain(|candidate| candidate % block_size.max(1) == 0);
+    candidates
+}
+
+// A sequential fill that went round the real capacity several times leaves
+// each block holding its last lap, so the distance to the data found is only
+// a multiple of the wrap. `reads_same` re-reads the block that many bytes
+// further on and says whether it came back unchanged; the smallest modulus
+// dividing the distance for which it does is the wrap. Returns that second
+// observation, which brings the common divisor of the samples down to it.
+pub fn nearer_alias(
+    sector: MisplacedSector,
+    block_size: u64,
+    total_bytes: u64,
+    mut reads_same: impl FnMut(u64) -> bool,
+) -> Option<MisplacedSector> {
+    let distance = sector.read_offset.abs_diff(sector.written_offset);
+    alias_moduli(block_size, total_bytes)
+        .into_iter()
+        .filter(|&modulus| modulus < distance && distance.is_multiple_of(modulus))
+        .find(|&modulus| reads_same(modulus))
+        .map(|modulus| MisplacedSector {
+            read_offset: sector.read_offset + modulus,
+            written_offset: sector.written_offset,
+        })
+}
+
+fn alias_moduli(block_size: u64, total_bytes: u64) -> Vec<u64> {
+    let mut moduli = Vec::new();
+    let mut modulus = block_size.max(1).next_power_of_two();
+    while modulus < total_bytes {
+        moduli.push(modulus);
+        modulus <<= 1;
+    }
+    let mut modulus = GIB;
+    while modulus < total_bytes {
+        moduli.push(modulus);
+        modulus += GIB;
+    }
+    moduli.sort_unstable();
+    moduli.dedup();
+    moduli
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+pub fn wrap_modulus(samples: &[MisplacedSector]) -> Option<u64> {
+    let modulus = samples
+        .iter()
+        .map(|sample| sample.read_offset.abs_diff(sample.written_offset))
+        .fold(0, gcd);
+    (modulus > 0).then_some(modulus)
+}
+
+fn status_severity(status: DriveHealthStatus) -> u8 {
+    match status {
+        DriveHealthStatus::Healthy => 0,
+        DriveHealthStatus::Degraded => 1,
+        DriveHealthStatus::PhysicalCorruption => 2,
+        DriveHealthStatus::Unstable => 3,
+        DriveHealthStatus::DataLoss => 4,
+        DriveHealthStatus::Disconnected => 5,
+        DriveHealthStatus::StaleData => 6,
+        DriveHealthStatus::ReadOnly => 7,
+        DriveHealthStatus::FakeCapacity => 8,
+    }
+}
+
+pub fn generate_report(
+    total_capacity: u64,
+    tested_bytes: u64,
+    stats: &ScanStats,
+    pattern: Pattern,
+) -> DiagnosisReport {
+    let error_count = stats.mismatch_blocks + stats.read_error_blocks + stats.write_error_blocks;
+    let mut status = if error_count == 0 {
+        DriveHealthStatus::Healthy
+    } else {
+        DriveHealthStatus::PhysicalCorruption
+    };
+
+    if stats.read_error_blocks > 0 || stats.write_error_blocks > 0 {
+        status = DriveHealthStatus::DataLoss;
+    }
+
+    if let Some(sample) = stats.sample_status
+        && status_severity(sample) > status_severity(status)
+    {
+        status = sample;
+    }
+
+    // A large enough cache lifts the average speed of a drive that cannot keep
+    // it up, so the speed after the cache fills counts as well.
+    let write_cache = match stats.write_cache_skip {
+        Some(_) => None,
+        None => estimate_write_cache(&stats.write_throughput),
+    };
+    let slowest = [
+        stats.write_speed_mbps,
+        stats.verify_speed_mbps,
+        write_cache.map(|cache| cache.sustained_mbps),
+    ]
+    .into_iter()
+    .flatten()
+    .reduce(f64::min);
+    // A drive that hangs now and then can still average a fine speed.
+    let stalls = stats.write_latency.stalls + stats.read_latency.stalls;
+    if status == DriveHealthStatus::Healthy
+        && (slowest.is_some_and(|speed| speed < DEGRADED_SPEED_MBPS) || stalls > 0)
+    {
+        status = DriveHealthStatus::Degraded;
+    }
+
+    let health_score = if total_capacity == 0 {
+        0.0
+    } else {
+        let ratio = stats.valid_bytes as f64 / total_capacity as f64;
+        (ratio * 100.0).clamp(0.0, 100.0)
+    };
+
+    let wrap_modulus = wrap_modulus(&stats.misplaced_sectors);
+    let mut bit_errors = stats.bit_errors.clone();
+    bit_errors.finalize(tested_bytes);
+    let usable_capacity = match (stats.highest_valid_end, wrap_modulus) {
+        (0, _) => None,
+        (end, Some(modulus)) => Some(end.min(modulus)),
+        (end, None) => Some(end),
+    };
+
+    let base = match status {
+        DriveHealthStatus::Healthy => "No inconsistencies detected.",
+        DriveHealthStatus::FakeCapacity => {
+            "Warning: drive returned fill data or data from other offsets; possible fake capacity."
+        }
+        DriveHealthStatus::PhysicalCorruption => {
+            "Warning: detected random corruption or inconsistent data."
+        }
+        DriveHealthStatus::DataLoss => "Warning: read errors or missing data detected.",
+        DriveHealthStatus::StaleData => {
+            "Warning: drive returned data that was never written by this test."
+        }
+        DriveHealthStatus::Degraded => {
+            "Data verified, but the drive is far too slow for normal use."
+        }
+        DriveHealthStatus::ReadOnly => "Warning: drive is write-protected or mounted read-only.",
+        DriveHealthStatus::Disconnected => "Warning: drive disconnected during the test.",
+        DriveHealthStatus::Unstable => "Warning: drive returned different data on re-read.",
+    };
+
+    let mut conclusion = format!("{base} Errors: {error_count}.");
+    if tested_bytes < total_capacity {
+        conclusion.push_str(&format!(
+            " Verification ended early after {tested_bytes} / {total_capacity} bytes."
+        ));
+    }
+    if let Some(sector) = stats.misplaced_sectors.first() {
+        conclusion.push_str(&format!(
+            " Offset {} returned the data written at offset {}.",
+            sector.read_offset, sector.written_offset
+        ));
+    }
+    if status == DriveHealthStatus::FakeCapacity
+        && let Some(usable) = usable_capacity
+    {
+        conclusion.push_str(&format!(" Usable capacity: {usable} bytes."));
+    }
+    if let Some(modulus) = wrap_modulus {
+        conclusion.push_str(&format!(" Address space wraps every {modulus} bytes."));
+    }
+    if stats.write_error_blocks > 0 {
+        conclusion.push_str(&format!(
+            " {} blocks could not be written.",
+            stats.write_error_blocks
+        ));
+    }
+    if stats.recovered_reads > 0 {
+        conclusion.push_str(&format!(
+            " {} blocks could only be read after a retry.",
+            stats.recovered_reads
+        ));
+    }
+    if stalls > 0 {
+        let threshold_us = stats
+            .write_latency
+            .stall_threshold_us
+            .max(stats.read_latency.stall_threshold_us);
+        conclusion.push_str(&format!(
+            " {stalls} I/O requests stalled for over {} s.",
+            threshold_us as f64 / 1_000_000.0
+        ));
+    }
+    if let Some(cache) = write_cache {
+        conclusion.push_str(&format!(
+            " Write speed dropped from {:.0} to {:.0} MB/s after {} bytes.",
+            cache.cached_mbps, cache.sustained_mbps, cache.cache_bytes
+        ));
+    }
+    match stats.write_cache_skip {
+        Some(CacheEstimateSkip::NonSequentialWrite) => conclusion.push_str(
+            " The write cache was not estimated since the data was not written sequentially.",
+        ),
+        Some(CacheEstimateSkip::ResumedWrite) => {
+            conclusion.push_str(" The write cache was not estimated since the write was resumed.")
+        }
+        None => {}
+    }
+    if stats.buffered_io {
+        conclusion
+            .push_str(" Direct I/O was unavailable; data may have been read from the page cache.");
+    }
+
+    DiagnosisReport {
+        total_capacity,
+        tested_bytes,
+        valid_bytes: stats.valid_bytes,
+        corrupted_bytes: stats.corrupted_bytes,
+        error_count,
+        write_error_blocks: stats.write_error_blocks,
+        health_score,
+        status,
+        conclusion,
+        session_seed: pattern.session_seed,
+        pattern_generator: pattern.generator.name(),
+        misplaced_sectors: stats.misplaced_sectors.clone(),
+        usable_capacity,
+        wrap_modulus,
+        bit_errors,
+        failures: stats.failures,
+        unstable_blocks: stats.unstable_blocks,
+        recovered_reads: stats.recovered_reads,
+        write_speed_mbps: stats.write_speed_mbps,
+        verify_speed_mbps: stats.verify_speed_mbps,
+        write_latency: stats.write_latency.clone(),
+        read_latency: stats.read_latency.clone(),
+        write_throughput: stats.write_throughput.clone(),
+        read_throughput: stats.read_throughput.clone(),
+        write_cache,
+        write_cache_skip: stats.write_cache_skip,
+        bad_ranges: bad_blocks::sorted_bad_ranges(&stats.bad_ranges),
+        geometry: stats.geometry,
+        buffered_io: stats.buffered_io,
+        passes: Vec::new(),
+    }
+}
+
+// Folds the reports of a multi-pass run into one. The worst pass decides the
+// status and conclusion; errors, bad ranges and bit flips add up over all
+// passes, since a bit stuck at one value only shows in the patterns that need
+// the other.
+pub fn combine_pass_reports(reports: &[DiagnosisReport]) -> Option<DiagnosisReport> {
+    let worst = reports
+        .iter()
+        .reduce(|worst, report| {
+            if status_severity(report.status) > status_severity(worst.status) {
+                report
+            } else {
+                worst
+            }
+        })?
+        .clone();
+    let mut combined = DiagnosisReport {
+        tested_bytes: reports.iter().map(|report| report.tested_bytes).min()?,
+        valid_bytes: reports.iter().map(|report| report.valid_bytes).min()?,
+        error_count: reports.iter().map(|report| report.error_count).sum(),
+        write_error_blocks: reports.iter().map(|report| report.write_error_blocks).sum(),
+        recovered_reads: reports.iter().map(|report| report.recovered_reads).sum(),
+        health_score: reports
+            .iter()
+            .map(|report| report.health_score)
+            .fold(f64::INFINITY, f64::min),
+        bit_errors: BitErrorStats::default(),
+        write_latency: LatencyStats::default(),
+        read_latency: LatencyStats::default(),
+        bad_ranges: bad_blocks::sorted_bad_ranges(
+            reports.iter().flat_map(|report| &report.bad_ranges),
+        ),
+        ..worst
+    };
+    let mut checked_bytes = 0;
+    for report in reports {
+        combined.bit_errors.merge(&report.bit_errors);
+        combined.write_latency.merge(&report.write_latency);
+        combined.read_latency.merge(&report.read_latency);
+        checked_bytes += report.tested_bytes;
+        combined.passes.push(PassReport {
+            pattern_generator: report.pattern_generator,
+            status: report.status,
+            tested_bytes: report.tested_bytes,
+            error_count: report.error_count,
+            corrupted_bytes: report.corrupted_bytes,
+            write_speed_mbps: report.write_speed_mbps,
+            verify_speed_mbps: report.verify_speed_mbps,
+        });
+    }
+    combined.bit_errors.finalize(checked_bytes);
+    Some(combined)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{
+        ALTERNATING, BUILTIN_GENERATORS, BadRange, BadRangeKind, CHACHA20, CacheEstimateSkip,
+        DriveHealthStatus, MisplacedSector, Pattern, PatternLayout, SECTOR_SIZE, SPLITMIX,
+        ScanStats, ThroughputCurve, ZEROS, alias_candidates, combine_pass_reports,
+        decode_sector_header, fill_block, find_matching_offset, find_misplaced_sector,
+        generate_report, generate_session_seed, inverse_generator, mismatch_extents, nearer_alias,
+        verify_block, wrap_modulus,
+    };
+
+    const SEED: u64 = 0x5EED_5EED_5EED_5EED;
+    const SESSION: Pattern = Pattern {
+        session_seed: SEED,
+        layout: PatternLayout::Plain,
+        generator: &SPLITMIX,
+    };
+    const HEADERS: Pattern = Pattern {
+        session_seed: SEED,
+        layout: PatternLayout::SectorHeaders,
+        generator: &SPLITMIX,
+    };
+
+    #[test]
+    fn test_determinism() {
+        let mut block1 = vec![0u8; 1024];
+        let mut block2 = vec![0u8; 1024];
+        fill_block(SESSION, 100, &mut block1);
+        fill_block(SESSION, 100, &mut block2);
+        assert_eq!(block1, block2);
+    }
+
+    #[test]
+    fn test_offset_variance() {
+        let mut block1 = vec![0u8; 1024];
+        let mut block2 = vec![0u8; 1024];
+        fill_block(SESSION, 100, &mut block1);
+        fill_block(SESSION, 200, &mut block2);
+        assert_ne!(block1, block2);
+    }
+
+    #[test]
+    fn test_seed_separation() {
+        use super::generators::generate_seed;
+
+        let seed_a = generate_seed(SEED, 0);
+        let seed_b = generate_seed(SEED, 4 * 1024 * 1024 * 1024);
+        assert_ne!(seed_a, seed_b);
+    }
+
+    #[test]
+    fn test_verification_logic() {
+        let offset = 500;
+        let mut data = vec![0u8; 256];
+
+        fill_block(SESSION, offset, &mut data);
+        assert!(verify_block(SESSION, offset, &data).is_ok());
+
+        data[10] = data[10].wrapping_add(1);
+        assert_eq!(verify_block(SESSION, offset, &data), Err(10));
+    }
+
+    #[test]
+    fn test_partial_last_block_verification() {
+        let total_capacity: u64 = 1000;
+        let block_size: usize = 256;
+        let mut offset: u64 = 0;
+
+        while offset < total_capacity {
+            let remaining = total_capacity - offset;
+            let len = std::cmp::min(remaining, block_size as u64) as usize;
+            let mut data = vec![0u8; len];
+            fill_block(SESSION, offset, &mut data);
+            assert!(verify_block(SESSION, offset, &data).is_ok());
+            offset += len as u64;
+        }
+    }
+
+    #[test]
+    fn test_verify_block_reports_exact_index_on_bit_flip() {
+        let offset = 42;
+        let mut data = vec![0u8; 128];
+        fill_block(SESSION, offset, &mut data);
+
+        let flip_index = 77;
+        data[flip_index] ^= 0x01;
+
+        assert_eq!(verify_block(SESSION, offset, &data), Err(flip_index));
+    }
+
+    #[test]
+    fn test_session_seed_separates_runs() {
+        let offset = 4096;
+        let mut data = vec![0u8; 512];
+        fill_block(SESSION, offset, &mut data);
+        assert!(verify_block(SESSION, offset, &data).is_ok());
+        let other = Pattern::new(SEED ^ 1, PatternLayout::Plain, &SPLITMIX);
+        assert!(verify_block(other, offset, &data).is_err());
+    }
+
+    #[test]
+    fn test_generated_session_seeds_differ() {
+        assert_ne!(generate_session_seed(), generate_session_seed());
+    }
+
+    #[test]
+    fn test_sector_headers_round_trip() {
+        let offset = 3 * SECTOR_SIZE as u64;
+        let mut data = vec![0u8; 2 * SECTOR_SIZE];
+        fill_block(HEADERS, offset, &mut data);
+        assert!(verify_block(HEADERS, offset, &data).is_ok());
+        assert!(verify_block(SESSION, offset, &data).is_err());
+
+        let header = decode_sector_header(&data[SECTOR_SIZE..]).unwrap();
+        assert_eq!(header.session_seed, SEED);
+        assert_eq!(header.offset, offset + SECTOR_SIZE as u64);
+    }
+
+    #[test]
+    fn test_sector_header_rejects_corruption() {
+        let mut data = vec![0u8; SECTOR_SIZE];
+        fill_block(HEADERS, 0, &mut data);
+        data[17] ^= 0x40;
+        assert!(decode_sector_header(&data).is_none());
+    }
+
+    #[test]
+    fn test_misplaced_sector_reports_source_offset() {
+        let wrapped = 16 * SECTOR_SIZE as u64;
+        let mut data = vec![0u8; 2 * SECTOR_SIZE];
+        fill_block(HEADERS, 0, &mut data);
+
+        assert_eq!(verify_block(HEADERS, wrapped, &data), Err(18));
+        assert_eq!(
+            find_misplaced_sector(SEED, wrapped, &data),
+            Some(MisplacedSector {
+                read_offset: wrapped,
+                written_offset: 0,
+            })
+        );
+        assert_eq!(find_misplaced_sector(SEED ^ 1, wrapped, &data), None);
+    }
+
+    #[test]
+    fn test_alias_search_finds_wrapped_block() {
+        let block = 64 * 1024u64;
+        let total = 16 * block;
+        let mut data = vec![0u8; block as usize];
+        fill_block(SESSION, 13 * block, &mut data);
+
+        let candidates = alias_candidates(5 * block, block, block, total);
+        assert!(candidates.iter().all(|candidate| candidate % block == 0));
+        assert_eq!(
+            find_matching_offset(SESSION, &candidates, 5 * block, &data),
+            Some(13 * block)
+        );
+    }
+
+    #[test]
+    fn test_wrap_modulus_uses_common_divisor() {
+        let samples = [
+            MisplacedSector {
+                read_offset: 0,
+                written_offset: 24,
+            },
+            MisplacedSector {
+                read_offset: 40,
+                written_offset: 24,
+            },
+        ];
+        assert_eq!(wrap_modulus(&samples), Some(8));
+        assert_eq!(wrap_modulus(&[]), None);
+    }
+
+    #[test]
+    fn test_wrap_found_when_fill_went_round_several_times() {
+        // A drive that really holds 4 blocks, filled with 16: each block keeps
+        // the last of the four laps written over it.
+        let block = 64 * 1024u64;
+        let wrap = 4 * block;
+        let total = 16 * block;
+        let mut data = vec![0u8; block as usize];
+        fill_block(SESSION, 3 * wrap + block, &mut data);
+
+        let candidates = alias_candidates(block, block, block, total);
+        let written_offset = find_matching_offset(SESSION, &candidates, block, &data).unwrap();
+        assert_eq!(written_offset, 3 * wrap + block);
+        let sector = MisplacedSector {
+            read_offset: block,
+            written_offset,
+        };
+        let nearer = nearer_alias(sector, block, total, |modulus| modulus % wrap == 0).unwrap();
+        assert_eq!(nearer.read_offset, wrap + block);
+
+        let mut stats = ScanStats {
+            mismatch_blocks: 12,
+            sample_status: Some(DriveHealthStatus::FakeCapacity),
+            misplaced_sectors: vec![sector, nearer],
+            ..ScanStats::default()
+        };
+        stats.record_valid(3 * wrap, wrap);
+        let report = generate_report(total, total, &stats, SESSION);
+        assert_eq!(report.wrap_modulus, Some(wrap));
+        assert_eq!(report.usable_capacity, Some(wrap));
+    }
+
+    #[test]
+    fn test_usable_capacity_clamped_to_wrap_modulus() {
+        let mut stats = ScanStats {
+            mismatch_blocks: 4,
+            sample_status: Some(DriveHealthStatus::FakeCapacity),
+            misplaced_sectors: vec![MisplacedSector {
+                read_offset: 0,
+                written_offset: 8192,
+            }],
+            ..ScanStats::default()
+        };
+        stats.record_valid(8192, 8192);
+
+        let report = generate_report(16384, 16384, &stats, SESSION);
+        assert_eq!(report.status, DriveHealthStatus::FakeCapacity);
+        assert_eq!(report.wrap_modulus, Some(8192));
+        assert_eq!(report.usable_capacity, Some(8192));
+    }
+
+    #[test]
+    fn test_sample_status_keeps_most_severe() {
+        let mut stats = ScanStats::default();
+        stats.record_status(DriveHealthStatus::Unstable);
+        stats.record_status(DriveHealthStatus::PhysicalCorruption);
+        assert_eq!(stats.sample_status, Some(DriveHealthStatus::Unstable));
+        stats.record_status(DriveHealthStatus::Disconnected);
+        assert_eq!(stats.sample_status, Some(DriveHealthStatus::Disconnected));
+    }
+
+    #[test]
+    fn test_slow_clean_run_is_degraded() {
+        let mut stats = ScanStats {
+            write_speed_mbps: Some(1.0),
+            verify_speed_mbps: Some(20.0),
+            ..ScanStats::default()
+        };
+        stats.record_valid(0, 8192);
+        let report = generate_report(8192, 8192, &stats, SESSION);
+        assert_eq!(report.status, DriveHealthStatus::Degraded);
+
+        stats.write_speed_mbps = Some(10.0);
+        let report = generate_report(8192, 8192, &stats, SESSION);
+        assert_eq!(report.status, DriveHealthStatus::Healthy);
+    }
+
+    #[test]
+    fn test_recovered_read_is_not_unstable() {
+        let mut stats = ScanStats {
+            recovered_reads: 1,
+            ..ScanStats::default()
+        };
+        stats.record_valid(0, 8192);
+        let report = generate_report(8192, 8192, &stats, SESSION);
+        assert_eq!(report.status, DriveHealthStatus::Healthy);
+        assert_eq!(report.recovered_reads, 1);
+    }
+
+    #[test]
+    fn test_cache_estimate_skipped_for_unusable_curve() {
+        let mut stats = ScanStats::default();
+        stats.record_valid(0, 8192);
+        let region = 64 * 1024 * 1024;
+        stats.write_throughput = ThroughputCurve::new(region);
+        for (index, speed) in [900.0, 900.0, 900.0, 150.0, 150.0, 150.0, 150.0, 150.0]
+            .into_iter()
+            .enumerate()
+        {
+            let took = Duration::from_secs_f64(64.0 / speed);
+            let elapsed = Duration::from_secs(index as u64);
+            stats
+                .write_throughput
+                .push((index as u64 + 1) * region, elapsed, region, took);
+        }
+        assert!(
+            generate_report(8192, 8192, &stats, SESSION)
+                .write_cache
+                .is_some()
+        );
+
+        stats.write_cache_skip = Some(CacheEstimateSkip::NonSequentialWrite);
+        let report = generate_report(8192, 8192, &stats, SESSION);
+        assert!(report.write_cache.is_none());
+        assert_eq!(
+            report.write_cache_skip,
+            Some(CacheEstimateSkip::NonSequentialWrite)
+        );
+        assert!(report.conclusion.contains("not written sequentially"));
+    }
+
+    #[test]
+    fn test_write_failure_is_data_loss() {
+        let mut stats = ScanStats::default();
+        stats.record_valid(0, 4096);
+        stats.record_write_error(4096, 4096);
+        let report = generate_report(8192, 8192, &stats, SESSION);
+        assert_eq!(report.status, DriveHealthStatus::DataLoss);
+        assert_eq!(report.write_error_blocks, 1);
+        assert_eq!(report.error_count, 1);
+        assert_eq!(stats.bad_ranges[0].kind, BadRangeKind::WriteError);
+    }
+
+    #[test]
+    fn test_merged_regions_match_one_sequential_scan() {
+        let mut first = ScanStats::default();
+        first.record_valid(0, 4096);
+        first.record_read_error(4096, 4096);
+        let mut second = ScanStats::default();
+        second.record_read_error(8192, 4096);
+        second.record_valid(12288, 4096);
+        second.record_status(DriveHealthStatus::Unstable);
+
+        first.merge(&second);
+        assert_eq!(first.valid_bytes, 8192);
+        assert_eq!(first.read_error_blocks, 2);
+        assert_eq!(first.highest_valid_end, 16384);
+        assert_eq!(first.sample_status, Some(DriveHealthStatus::Unstable));
+        assert_eq!(
+            first.bad_ranges,
+            vec![BadRange {
+                start: 4096,
+                end: 12288,
+                kind: BadRangeKind::ReadError,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_splitmix_output_is_stable() {
+        let mut data = [0u8; 16];
+        fill_block(
+            Pattern::new(0, PatternLayout::Plain, &SPLITMIX),
+            0,
+            &mut data,
+        );
+        assert_eq!(
+            data,
+            [
+                0x6F, 0x7E, 0x19, 0x4D, 0x2F, 0xDD, 0x06, 0xA7, 0x5E, 0x4F, 0x41, 0xF4, 0x05, 0xA3,
+                0x82, 0xB3
+            ]
+        );
+    }
+
+    #[test]
+    fn test_every_builtin_generator_round_trips() {
+        for &generator in BUILTIN_GENERATORS.iter() {
+            let pattern = Pattern::new(SEED, PatternLayout::Plain, generator);
+            let mut data = vec![0u8; 1000];
+            fill_block(pattern, 8192, &mut data);
+            assert!(
+                verify_block(pattern, 8192, &data).is_ok(),
+                "{}",
+                generator.name()
+            );
+            data[999] ^= 0x80;
+            assert_eq!(
+                verify_block(pattern, 8192, &data),
+                Err(999),
+                "{}",
+                generator.name()
+            );
+        }
+    }
+
+    #[test]
+    fn test_passes_combine_into_the_worst_result() {
+        let inverse = inverse_generator(&SPLITMIX).unwrap();
+        assert_eq!(inverse_generator(inverse).unwrap().name(), "splitmix");
+        let mut data = [0u8; 16];
+        let mut complement = [0u8; 16];
+        fill_block(SESSION, 0, &mut data);
+        fill_block(
+            Pattern::new(SEED, PatternLayout::Plain, inverse),
+            0,
+            &mut complement,
+        );
+        assert!(data.iter().zip(&complement).all(|(a, b)| *a == !*b));
+
+        let mut stats = ScanStats::default();
+        stats.record_valid(0, 8192);
+        let clean = generate_report(8192, 8192, &stats, SESSION);
+        stats.record_read_error(4096, 4096);
+        let failed = generate_report(8192, 8192, &stats, SESSION);
+        let report = combine_pass_reports(&[clean.clone(), failed, clean]).unwrap();
+        assert_eq!(report.status, DriveHealthStatus::DataLoss);
+        assert_eq!(report.error_count, 1);
+        assert_eq!(report.passes.len(), 3);
+        assert_eq!(report.passes[0].status, DriveHealthStatus::Healthy);
+        assert_eq!(report.bad_ranges.len(), 1);
+    }
+
+    #[test]
+    fn test_fixed_generators_emit_constant_bytes() {
+        let mut data = [0xEEu8; 6];
+        fill_block(
+            Pattern::new(SEED, PatternLayout::Plain, &ZEROS),
+            0,
+            &mut data,
+        );
+        assert_eq!(data, [0x00; 6]);
+        fill_block(
+            Pattern::new(SEED, PatternLayout::Plain, &ALTERNATING),
+            0,
+            &mut data,
+        );
+        assert_eq!(data, [0x55, 0xAA, 0x55, 0xAA, 0x55, 0xAA]);
+    }
+
+    #[test]
+    fn test_chacha20_depends_on_seed_and_offset() {
+        let mut base = vec![0u8; 256];
+        let mut other_seed = vec![0u8; 256];
+        let mut other_offset = vec![0u8; 256];
+        fill_block(
+            Pattern::new(SEED, PatternLayout::Plain, &CHACHA20),
+            0,
+            &mut base,
+        );
+        fill_block(
+            Pattern::new(SEED ^ 1, PatternLayout::Plain, &CHACHA20),
+            0,
+            &mut other_seed,
+        );
+        fill_block(
+            Pattern::new(SEED, PatternLayout::Plain, &CHACHA20),
+            4096,
+            &mut other_offset,
+        );
+        assert_ne!(base, other_seed);
+        assert_ne!(base, other_offset);
+    }
+
+    #[test]
+    fn test_verify_reports_index_past_first_chunk() {
+        let offset = 12 * SECTOR_SIZE as u64;
+        let mut data = vec![0u8; 3 * SECTOR_SIZE + 13];
+        fill_block(HEADERS, offset, &mut data);
+        assert!(verify_block(HEADERS, offset, &data).is_ok());
+
+        for flip in [SECTOR_SIZE + 5, 2 * SECTOR_SIZE + 3, data.len() - 1] {
+            let mut corrupted = data.clone();
+            corrupted[flip] ^= 0x02;
+            assert_eq!(verify_block(HEADERS, offset, &corrupted), Err(flip));
+        }
+    }
+
+    #[test]
+    fn test_mismatch_extents_cover_every_bad_range() {
+        let offset = 8192;
+        let mut data = vec![0u8; 10_000];
+        fill_block(SESSION, offset, &mut data);
+        assert!(mismatch_extents(SESSION, offset, &data).is_empty());
+
+        data[4090..4100].iter_mut().for_each(|byte| *byte ^= 0xFF);
+        data[9_999] ^= 0x01;
+        assert_eq!(
+            mismatch_extents(SESSION, offset, &data),
+            vec![4090..4100, 9_999..10_000]
+        );
+    }
+
+    #[test]
+    fn test_partial_mismatch_keeps_valid_bytes() {
+        let mut stats = ScanStats::default();
+        stats.record_valid(0, 4096);
+        stats.record_mismatch(4096, 4096, &[10..20, 100..101]);
+
+        let report = generate_report(8192, 8192, &stats, SESSION);
+        assert_eq!(
+            report.bad_ranges,
+            vec![
+                BadRange {
+                    start: 4106,
+                    end: 4116,
+                    kind: BadRangeKind::Mismatch,
+                },
+                BadRange {
+                    start: 4196,
+                    end: 4197,
+                    kind: BadRangeKind::Mismatch,
+                },
+            ]
+        );
+        assert_eq!(report.corrupted_bytes, 11);
+        assert_eq!(report.valid_bytes, 8192 - 11);
+        assert_eq!(report.error_count, 1);
+    }
+}
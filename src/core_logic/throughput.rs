@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+use serde::Serialize;
+
+const MIN_CACHE_POINTS: usize = 4;
+const CACHE_SPEEDUP: f64 = 1.5;
+const DROP_CONFIRM_POINTS: usize = 3;
+
+// `offset` is where the region ends, counted in bytes done in the order they
+// completed, which is the file offset for a sequential pass. `elapsed_secs`
+// is measured from the start of the phase.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ThroughputPoint {
+    pub offset: u64,
+    pub elapsed_secs: f64,
+    pub speed_mbps: f64,
+}
+
+// Speed over each `region_bytes` of a phase. Unlike the average speed it shows
+// where a drive slows down, e.g. once its fast write cache is full.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ThroughputCurve {
+    pub region_bytes: u64,
+    pub points: Vec<ThroughputPoint>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct WriteCacheEstimate {
+    pub cache_bytes: u64,
+    pub cached_mbps: f64,
+    pub sustained_mbps: f64,
+}
+
+// Why the write curve was not used to estimate a cache. Its points only sit at
+// file offsets for a sequential write, and a resumed write has lost the fast
+// start of the curve to the earlier session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheEstimateSkip {
+    NonSequentialWrite,
+    ResumedWrite,
+}
+
+impl ThroughputCurve {
+    pub fn new(region_bytes: u64) -> Self {
+        Self {
+            region_bytes,
+            points: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, offset: u64, elapsed: Duration, bytes: u64, took: Duration) {
+        let seconds = took.as_secs_f64();
+        let speed_mbps = if seconds > 0.0 {
+            bytes as f64 / 1024.0 / 1024.0 / seconds
+        } else {
+            0.0
+        };
+        self.points.push(ThroughputPoint {
+            offset,
+            elapsed_secs: elapsed.as_secs_f64(),
+            speed_mbps,
+        });
+    }
+}
+
+// A drive with a fast cache in front of slow flash writes well above the speed
+// it can keep up, then drops once the cache is full. The sustained speed is
+// the median of the last quarter of the curve. The cache ends before the first
+// region that falls below halfway between the opening and sustained speeds and
+// stays there for the next few regions.
+pub fn estimate_write_cache(curve: &ThroughputCurve) -> Option<WriteCacheEstimate> {
+    let speeds: Vec<f64> = curve.points.iter().map(|point| point.speed_mbps).collect();
+    if speeds.len() < MIN_CACHE_POINTS {
+        return None;
+    }
+    let sustained_mbps = median(&speeds[speeds.len() - speeds.len().div_ceil(4)..]);
+    let opening = speeds
+        .iter()
+        .take(DROP_CONFIRM_POINTS)
+        .copied()
+        .fold(0.0, f64::max);
+    let threshold = (opening + sustained_mbps) / 2.0;
+    let drop = (1..speeds.len()).find(|&index| {
+        speeds[index..]
+            .iter()
+            .take(DROP_CONFIRM_POINTS)
+            .all(|&speed| speed < threshold)
+    })?;
+    let cached_mbps = median(&speeds[..drop]);
+    (cached_mbps >= sustained_mbps * CACHE_SPEEDUP).then(|| WriteCacheEstimate {
+        cache_bytes: curve.points[drop - 1].offset,
+        cached_mbps,
+        sustained_mbps,
+    })
+}
+
+fn median(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable_by(f64::total_cmp);
+    let middle = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[middle - 1] + sorted[middle]) / 2.0
+    } else {
+        sorted[middle]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{ThroughputCurve, estimate_write_cache};
+
+    const REGION: u64 = 64 * 1024 * 1024;
+
+    fn curve(speeds: &[u64]) -> ThroughputCurve {
+        let mut curve = ThroughputCurve::new(REGION);
+        let mut elapsed = Duration::ZERO;
+        for (index, &speed) in speeds.iter().enumerate() {
+            let took = Duration::from_secs_f64(64.0 / speed as f64);
+            elapsed += took;
+            curve.push((index as u64 + 1) * REGION, elapsed, REGION, took);
+        }
+        curve
+    }
+
+    #[test]
+    fn test_estimates_write_cache_from_speed_drop() {
+        let cached = curve(&[
+            880, 920, 910, 900, 150, 900, 140, 145, 150, 150, 148, 152, 150,
+        ]);
+        let estimate = estimate_write_cache(&cached).unwrap();
+        assert_eq!(estimate.cache_bytes, 6 * REGION);
+        assert!((estimate.cached_mbps - 900.0).abs() < 1.0);
+        assert!((estimate.sustained_mbps - 150.0).abs() < 1.0);
+
+        assert!(estimate_write_cache(&curve(&[300, 310, 290, 305, 300, 295])).is_none());
+        assert!(estimate_write_cache(&curve(&[900, 150])).is_none());
+    }
+}
@@ -1,6 +1,6 @@
 use std::env;
 
-use crate::core_logic::{DiagnosisReport, DriveHealthStatus};
+use crate::core_logic::{CacheEstimateSkip, DiagnosisReport, DriveHealthStatus};
 
 #[derive(Copy, Clone)]
 pub enum Locale {
@@ -33,10 +33,10 @@ fn parse_locale(value: &str) -> Option<Locale> {
 
 pub fn detect_locale() -> Locale {
     for key in ["LC_ALL", "LC_MESSAGES", "LANG"] {
-        if let Ok(value) = env::var(key) {
-            if let Some(locale) = parse_locale(&value) {
-                return locale;
-            }
+        if let Ok(value) = env::var(key)
+            && let Some(locale) = parse_locale(&value)
+        {
+            return locale;
         }
     }
     Locale::En
@@ -48,18 +48,38 @@ pub fn status_label(locale: Locale, status: DriveHealthStatus) -> &'static str {
         (Locale::ZhCn, DriveHealthStatus::FakeCapacity) => "虚假容量",
         (Locale::ZhCn, DriveHealthStatus::PhysicalCorruption) => "物理损坏",
         (Locale::ZhCn, DriveHealthStatus::DataLoss) => "数据丢失",
+        (Locale::ZhCn, DriveHealthStatus::StaleData) => "陈旧数据",
+        (Locale::ZhCn, DriveHealthStatus::Degraded) => "性能降级",
+        (Locale::ZhCn, DriveHealthStatus::ReadOnly) => "只读",
+        (Locale::ZhCn, DriveHealthStatus::Disconnected) => "已断开",
+        (Locale::ZhCn, DriveHealthStatus::Unstable) => "读取不稳定",
         (Locale::ZhTw, DriveHealthStatus::Healthy) => "健康",
         (Locale::ZhTw, DriveHealthStatus::FakeCapacity) => "虛假容量",
         (Locale::ZhTw, DriveHealthStatus::PhysicalCorruption) => "實體損壞",
         (Locale::ZhTw, DriveHealthStatus::DataLoss) => "資料遺失",
+        (Locale::ZhTw, DriveHealthStatus::StaleData) => "陳舊資料",
+        (Locale::ZhTw, DriveHealthStatus::Degraded) => "效能降級",
+        (Locale::ZhTw, DriveHealthStatus::ReadOnly) => "唯讀",
+        (Locale::ZhTw, DriveHealthStatus::Disconnected) => "已中斷連線",
+        (Locale::ZhTw, DriveHealthStatus::Unstable) => "讀取不穩定",
         (Locale::Ja, DriveHealthStatus::Healthy) => "健全",
         (Locale::Ja, DriveHealthStatus::FakeCapacity) => "偽容量",
         (Locale::Ja, DriveHealthStatus::PhysicalCorruption) => "物理破損",
         (Locale::Ja, DriveHealthStatus::DataLoss) => "データ損失",
+        (Locale::Ja, DriveHealthStatus::StaleData) => "古いデータ",
+        (Locale::Ja, DriveHealthStatus::Degraded) => "性能低下",
+        (Locale::Ja, DriveHealthStatus::ReadOnly) => "読み取り専用",
+        (Locale::Ja, DriveHealthStatus::Disconnected) => "切断",
+        (Locale::Ja, DriveHealthStatus::Unstable) => "読み取り不安定",
         (_, DriveHealthStatus::Healthy) => "Healthy",
         (_, DriveHealthStatus::FakeCapacity) => "FakeCapacity",
         (_, DriveHealthStatus::PhysicalCorruption) => "PhysicalCorruption",
         (_, DriveHealthStatus::DataLoss) => "DataLoss",
+        (_, DriveHealthStatus::StaleData) => "StaleData",
+        (_, DriveHealthStatus::Degraded) => "Degraded",
+        (_, DriveHealthStatus::ReadOnly) => "ReadOnly",
+        (_, DriveHealthStatus::Disconnected) => "Disconnected",
+        (_, DriveHealthStatus::Unstable) => "Unstable",
     }
 }
 
@@ -72,10 +92,14 @@ pub fn localize_report_conclusion(locale: Locale, report: &DiagnosisReport) -> S
             Locale::Ja => "不整合は検出されませんでした。",
         },
         DriveHealthStatus::FakeCapacity => match locale {
-            Locale::En => "Warning: detected zero-filled data; possible fake capacity.",
-            Locale::ZhCn => "警告：检测到全零数据，可能为虚假容量。",
-            Locale::ZhTw => "警告：偵測到全零資料，可能為虛假容量。",
-            Locale::Ja => "警告：ゼロ埋めデータを検出。偽容量の可能性があります。",
+            Locale::En => {
+                "Warning: drive returned fill data or data from other offsets; possible fake capacity."
+            }
+            Locale::ZhCn => "警告：设备返回了填充数据或其他偏移的数据，可能为虚假容量。",
+            Locale::ZhTw => "警告：裝置傳回了填充資料或其他位移的資料，可能為虛假容量。",
+            Locale::Ja => {
+                "警告：埋め草データまたは別オフセットのデータが返されました。偽容量の可能性があります。"
+            }
         },
         DriveHealthStatus::PhysicalCorruption => match locale {
             Locale::En => "Warning: detected random corruption or inconsistent data.",
@@ -89,6 +113,38 @@ pub fn localize_report_conclusion(locale: Locale, report: &DiagnosisReport) -> S
             Locale::ZhTw => "警告：偵測到讀取錯誤或資料遺失。",
             Locale::Ja => "警告：読み取りエラーまたはデータ欠落を検出しました。",
         },
+        DriveHealthStatus::StaleData => match locale {
+            Locale::En => "Warning: drive returned data that was never written by this test.",
+            Locale::ZhCn => "警告：设备返回了本次测试从未写入的数据。",
+            Locale::ZhTw => "警告：裝置傳回了本次測試從未寫入的資料。",
+            Locale::Ja => "警告：このテストで書き込んでいないデータが返されました。",
+        },
+        DriveHealthStatus::Degraded => match locale {
+            Locale::En => "Data verified, but the drive is far too slow for normal use.",
+            Locale::ZhCn => "数据校验通过，但设备速度过慢，无法正常使用。",
+            Locale::ZhTw => "資料驗證通過，但裝置速度過慢，無法正常使用。",
+            Locale::Ja => "データは検証されましたが、通常の使用には速度が遅すぎます。",
+        },
+        DriveHealthStatus::ReadOnly => match locale {
+            Locale::En => "Warning: drive is write-protected or mounted read-only.",
+            Locale::ZhCn => "警告：设备处于写保护状态或以只读方式挂载。",
+            Locale::ZhTw => "警告：裝置處於防寫狀態或以唯讀方式掛載。",
+            Locale::Ja => {
+                "警告：ドライブが書き込み禁止、または読み取り専用でマウントされています。"
+            }
+        },
+        DriveHealthStatus::Disconnected => match locale {
+            Locale::En => "Warning: drive disconnected during the test.",
+            Locale::ZhCn => "警告：测试过程中设备已断开连接。",
+            Locale::ZhTw => "警告：測試過程中裝置已中斷連線。",
+            Locale::Ja => "警告：テスト中にドライブが切断されました。",
+        },
+        DriveHealthStatus::Unstable => match locale {
+            Locale::En => "Warning: drive returned different data on re-read.",
+            Locale::ZhCn => "警告：重复读取时设备返回了不同的数据。",
+            Locale::ZhTw => "警告：重複讀取時裝置傳回了不同的資料。",
+            Locale::Ja => "警告：再読み込みで異なるデータが返されました。",
+        },
     };
 
     let mut conclusion = match locale {
@@ -119,6 +175,151 @@ pub fn localize_report_conclusion(locale: Locale, report: &DiagnosisReport) -> S
         }
     }
 
+    if let Some(sector) = report.misplaced_sectors.first() {
+        match locale {
+            Locale::En => conclusion.push_str(&format!(
+                " Offset {} returned the data written at offset {}.",
+                sector.read_offset, sector.written_offset
+            )),
+            Locale::ZhCn => conclusion.push_str(&format!(
+                " 偏移 {} 读回了写入偏移 {} 的数据。",
+                sector.read_offset, sector.written_offset
+            )),
+            Locale::ZhTw => conclusion.push_str(&format!(
+                " 位移 {} 讀回了寫入位移 {} 的資料。",
+                sector.read_offset, sector.written_offset
+            )),
+            Locale::Ja => conclusion.push_str(&format!(
+                " オフセット {} からオフセット {} に書き込んだデータが返されました。",
+                sector.read_offset, sector.written_offset
+            )),
+        }
+    }
+
+    if report.status == DriveHealthStatus::FakeCapacity
+        && let Some(usable) = report.usable_capacity
+    {
+        conclusion.push_str(&match locale {
+            Locale::En => format!(" Usable capacity: {usable} bytes."),
+            Locale::ZhCn => format!(" 实际可用容量：{usable} 字节。"),
+            Locale::ZhTw => format!(" 實際可用容量：{usable} 位元組。"),
+            Locale::Ja => format!(" 実際の使用可能容量：{usable} バイト。"),
+        });
+    }
+
+    if let Some(modulus) = report.wrap_modulus {
+        conclusion.push_str(&match locale {
+            Locale::En => format!(" Address space wraps every {modulus} bytes."),
+            Locale::ZhCn => format!(" 地址空间每 {modulus} 字节循环一次。"),
+            Locale::ZhTw => format!(" 位址空間每 {modulus} 位元組循環一次。"),
+            Locale::Ja => format!(" アドレス空間は {modulus} バイトごとに折り返します。"),
+        });
+    }
+
+    let unwritten = report.write_error_blocks;
+    if unwritten > 0 {
+        conclusion.push_str(&match locale {
+            Locale::En => format!(" {unwritten} blocks could not be written."),
+            Locale::ZhCn => format!(" {unwritten} 个块无法写入。"),
+            Locale::ZhTw => format!(" {unwritten} 個區塊無法寫入。"),
+            Locale::Ja => format!(" {unwritten} 個のブロックに書き込めませんでした。"),
+        });
+    }
+
+    let recovered = report.recovered_reads;
+    if recovered > 0 {
+        conclusion.push_str(&match locale {
+            Locale::En => format!(" {recovered} blocks could only be read after a retry."),
+            Locale::ZhCn => format!(" {recovered} 个块需要重试才能读取。"),
+            Locale::ZhTw => format!(" {recovered} 個區塊需要重試才能讀取。"),
+            Locale::Ja => format!(" {recovered} 個のブロックは再試行後にのみ読み取れました。"),
+        });
+    }
+
+    let stalls = report.write_latency.stalls + report.read_latency.stalls;
+    if stalls > 0 {
+        let seconds = report
+            .write_latency
+            .stall_threshold_us
+            .max(report.read_latency.stall_threshold_us) as f64
+            / 1_000_000.0;
+        match locale {
+            Locale::En => conclusion.push_str(&format!(
+                " {stalls} I/O requests stalled for over {seconds} s."
+            )),
+            Locale::ZhCn => {
+                conclusion.push_str(&format!(" {stalls} 次 I/O 请求停顿超过 {seconds} 秒。"))
+            }
+            Locale::ZhTw => {
+                conclusion.push_str(&format!(" {stalls} 次 I/O 請求停頓超過 {seconds} 秒。"))
+            }
+            Locale::Ja => conclusion.push_str(&format!(
+                " {stalls} 件の I/O 要求が {seconds} 秒以上停止しました。"
+            )),
+        }
+    }
+
+    if let Some(cache) = &report.write_cache {
+        let (cached, sustained, bytes) =
+            (cache.cached_mbps, cache.sustained_mbps, cache.cache_bytes);
+        conclusion.push_str(&match locale {
+            Locale::En => format!(
+                " Write speed dropped from {cached:.0} to {sustained:.0} MB/s after {bytes} bytes."
+            ),
+            Locale::ZhCn => format!(
+                " 写入 {bytes} 字节后，写入速度从 {cached:.0} MB/s 降至 {sustained:.0} MB/s。"
+            ),
+            Locale::ZhTw => format!(
+                " 寫入 {bytes} 位元組後，寫入速度從 {cached:.0} MB/s 降至 {sustained:.0} MB/s。"
+            ),
+            Locale::Ja => format!(
+                " {bytes} バイト書き込んだ後、書き込み速度が {cached:.0} MB/s から {sustained:.0} MB/s に低下しました。"
+            ),
+        });
+    }
+
+    if let Some(skip) = report.write_cache_skip {
+        conclusion.push_str(match (skip, locale) {
+            (CacheEstimateSkip::NonSequentialWrite, Locale::En) => {
+                " The write cache was not estimated since the data was not written sequentially."
+            }
+            (CacheEstimateSkip::NonSequentialWrite, Locale::ZhCn) => {
+                " 数据未按顺序写入，因此未估算写入缓存。"
+            }
+            (CacheEstimateSkip::NonSequentialWrite, Locale::ZhTw) => {
+                " 資料未依序寫入，因此未估算寫入快取。"
+            }
+            (CacheEstimateSkip::NonSequentialWrite, Locale::Ja) => {
+                " データが順番に書き込まれなかったため、書き込みキャッシュは推定していません。"
+            }
+            (CacheEstimateSkip::ResumedWrite, Locale::En) => {
+                " The write cache was not estimated since the write was resumed."
+            }
+            (CacheEstimateSkip::ResumedWrite, Locale::ZhCn) => {
+                " 写入是续传的，因此未估算写入缓存。"
+            }
+            (CacheEstimateSkip::ResumedWrite, Locale::ZhTw) => {
+                " 寫入是續傳的，因此未估算寫入快取。"
+            }
+            (CacheEstimateSkip::ResumedWrite, Locale::Ja) => {
+                " 書き込みを再開したため、書き込みキャッシュは推定していません。"
+            }
+        });
+    }
+
+    if report.buffered_io {
+        conclusion.push_str(match locale {
+            Locale::En => {
+                " Direct I/O was unavailable; data may have been read from the page cache."
+            }
+            Locale::ZhCn => " 直接 I/O 不可用，读回的数据可能来自页缓存。",
+            Locale::ZhTw => " 直接 I/O 無法使用，讀回的資料可能來自頁面快取。",
+            Locale::Ja => {
+                " ダイレクト I/O が使用できないため、ページキャッシュから読み込まれた可能性があります。"
+            }
+        });
+    }
+
     conclusion
 }
 
@@ -158,6 +359,78 @@ pub fn total_label(locale: Locale) -> &'static str {
     }
 }
 
+pub fn bit_errors_label(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "位错误数",
+        Locale::ZhTw => "位元錯誤數",
+        Locale::Ja => "ビットエラー",
+        Locale::En => "Bit Errors",
+    }
+}
+
+pub fn geometry_label(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "扇区几何",
+        Locale::ZhTw => "磁區幾何",
+        Locale::Ja => "セクタ構成",
+        Locale::En => "Geometry",
+    }
+}
+
+pub fn latency_label(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "I/O 延迟",
+        Locale::ZhTw => "I/O 延遲",
+        Locale::Ja => "I/O レイテンシ",
+        Locale::En => "I/O Latency",
+    }
+}
+
+pub fn cli_invalid_stall(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "停顿阈值无效",
+        Locale::ZhTw => "停頓閾值無效",
+        Locale::Ja => "無効なストール閾値",
+        Locale::En => "Invalid stall threshold",
+    }
+}
+
+pub fn cli_invalid_curve_region(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "速度曲线区段大小无效",
+        Locale::ZhTw => "速度曲線區段大小無效",
+        Locale::Ja => "無効な速度曲線の区間サイズ",
+        Locale::En => "Invalid throughput curve region size",
+    }
+}
+
+pub fn write_cache_label(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "写入缓存",
+        Locale::ZhTw => "寫入快取",
+        Locale::Ja => "書き込みキャッシュ",
+        Locale::En => "Write Cache",
+    }
+}
+
+pub fn passes_label(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "测试轮次",
+        Locale::ZhTw => "測試輪次",
+        Locale::Ja => "パス",
+        Locale::En => "Passes",
+    }
+}
+
+pub fn bad_ranges_label(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "坏块区间",
+        Locale::ZhTw => "壞區塊區間",
+        Locale::Ja => "不良範囲",
+        Locale::En => "Bad Ranges",
+    }
+}
+
 pub fn status_header(locale: Locale) -> &'static str {
     match locale {
         Locale::ZhCn => "状态",
@@ -187,10 +460,18 @@ pub fn cli_intro(locale: Locale) -> &'static str {
 
 pub fn cli_usage(locale: Locale, binary: &str) -> String {
     let template = match locale {
-        Locale::ZhCn => "用法: {} <文件路径> [大小上限MB] [--force]",
-        Locale::ZhTw => "用法: {} <檔案路徑> [大小上限MB] [--force]",
-        Locale::Ja => "使い方: {} <ファイルパス> [サイズ上限MB] [--force]",
-        Locale::En => "Usage: {} <file_path> [size_limit_mb] [--force]",
+        Locale::ZhCn => {
+            "用法: {} <文件路径> [大小上限MB] [--force] [--device] [--sector-headers] [--pattern=<name>] [--badblocks=<file>] [--badblocks-block-size=<n>] [--engine=<blocking|io_uring>] [--queue-depth=<n>] [--verify-threads=<n>] [--write-order=<order>] [--verify-order=<order>] [--multi-file] [--segment-mb=<n>] [--reserve-mb=<n>] [--verify-only] [--seed=<hex>] [--resume] [--in-place] [--rollback] [--journal=<file>] [--passes=<pattern,...>] [--stall-secs=<n>] [--curve-region-mb=<n>]"
+        }
+        Locale::ZhTw => {
+            "用法: {} <檔案路徑> [大小上限MB] [--force] [--device] [--sector-headers] [--pattern=<name>] [--badblocks=<file>] [--badblocks-block-size=<n>] [--engine=<blocking|io_uring>] [--queue-depth=<n>] [--verify-threads=<n>] [--write-order=<order>] [--verify-order=<order>] [--multi-file] [--segment-mb=<n>] [--reserve-mb=<n>] [--verify-only] [--seed=<hex>] [--resume] [--in-place] [--rollback] [--journal=<file>] [--passes=<pattern,...>] [--stall-secs=<n>] [--curve-region-mb=<n>]"
+        }
+        Locale::Ja => {
+            "使い方: {} <ファイルパス> [サイズ上限MB] [--force] [--device] [--sector-headers] [--pattern=<name>] [--badblocks=<file>] [--badblocks-block-size=<n>] [--engine=<blocking|io_uring>] [--queue-depth=<n>] [--verify-threads=<n>] [--write-order=<order>] [--verify-order=<order>] [--multi-file] [--segment-mb=<n>] [--reserve-mb=<n>] [--verify-only] [--seed=<hex>] [--resume] [--in-place] [--rollback] [--journal=<file>] [--passes=<pattern,...>] [--stall-secs=<n>] [--curve-region-mb=<n>]"
+        }
+        Locale::En => {
+            "Usage: {} <file_path> [size_limit_mb] [--force] [--device] [--sector-headers] [--pattern=<name>] [--badblocks=<file>] [--badblocks-block-size=<n>] [--engine=<blocking|io_uring>] [--queue-depth=<n>] [--verify-threads=<n>] [--write-order=<order>] [--verify-order=<order>] [--multi-file] [--segment-mb=<n>] [--reserve-mb=<n>] [--verify-only] [--seed=<hex>] [--resume] [--in-place] [--rollback] [--journal=<file>] [--passes=<pattern,...>] [--stall-secs=<n>] [--curve-region-mb=<n>]"
+        }
     };
     template.replace("{}", binary)
 }
@@ -207,10 +488,18 @@ pub fn cli_example(locale: Locale, binary: &str) -> String {
 
 pub fn cli_note(locale: Locale) -> &'static str {
     match locale {
-        Locale::ZhCn => "说明: size_limit_mb 可选；0 表示不限制。",
-        Locale::ZhTw => "說明: size_limit_mb 可選；0 表示不限制。",
-        Locale::Ja => "注: size_limit_mb は任意。0 は無制限。",
-        Locale::En => "Note: size_limit_mb is optional; 0 means no limit.",
+        Locale::ZhCn => {
+            "说明: size_limit_mb 可选；0 表示按剩余空间自动确定。图案: splitmix, wyrand, zeros, ones, alternating, chacha20。"
+        }
+        Locale::ZhTw => {
+            "說明: size_limit_mb 可選；0 表示按剩餘空間自動決定。圖樣: splitmix, wyrand, zeros, ones, alternating, chacha20。"
+        }
+        Locale::Ja => {
+            "注: size_limit_mb は任意。0 は空き容量から自動で決定。パターン: splitmix, wyrand, zeros, ones, alternating, chacha20。"
+        }
+        Locale::En => {
+            "Note: size_limit_mb is optional; 0 sizes the test to the free space. Patterns: splitmix, wyrand, zeros, ones, alternating, chacha20."
+        }
     }
 }
 
@@ -232,6 +521,188 @@ pub fn cli_invalid_limit(locale: Locale) -> &'static str {
     }
 }
 
+pub fn cli_unknown_pattern(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "未知的测试图案",
+        Locale::ZhTw => "未知的測試圖樣",
+        Locale::Ja => "不明なテストパターン",
+        Locale::En => "Unknown pattern generator",
+    }
+}
+
+pub fn cli_badblocks_write_failed(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "无法写入坏块列表",
+        Locale::ZhTw => "無法寫入壞區塊清單",
+        Locale::Ja => "不良ブロックリストを書き込めません",
+        Locale::En => "Unable to write bad block list",
+    }
+}
+
+pub fn cli_invalid_badblocks_block_size(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "坏块列表块大小无效",
+        Locale::ZhTw => "壞區塊清單區塊大小無效",
+        Locale::Ja => "無効な不良ブロックリストのブロックサイズ",
+        Locale::En => "Invalid bad block list block size",
+    }
+}
+
+pub fn cli_badblocks_needs_device(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "坏块列表仅适用于块设备目标",
+        Locale::ZhTw => "壞區塊清單僅適用於區塊裝置目標",
+        Locale::Ja => "不良ブロックリストはブロックデバイスが対象の場合のみ出力できます",
+        Locale::En => "A bad block list needs a block device target",
+    }
+}
+
+pub fn cli_badblocks_block_size_unknown(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "无法确定设备块大小，请使用 --badblocks-block-size 指定",
+        Locale::ZhTw => "無法判斷裝置區塊大小，請使用 --badblocks-block-size 指定",
+        Locale::Ja => {
+            "デバイスのブロックサイズを判別できません。--badblocks-block-size で指定してください"
+        }
+        Locale::En => "Device block size is unknown; pass --badblocks-block-size",
+    }
+}
+
+pub fn cli_badblocks_written(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "已写入坏块列表",
+        Locale::ZhTw => "已寫入壞區塊清單",
+        Locale::Ja => "不良ブロックリストを書き込みました",
+        Locale::En => "Bad block list written",
+    }
+}
+
+pub fn cli_invalid_passes(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "无效的多轮测试计划",
+        Locale::ZhTw => "無效的多輪測試計畫",
+        Locale::Ja => "無効なマルチパス計画",
+        Locale::En => "Invalid pass plan",
+    }
+}
+
+pub fn cli_passes_conflict(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "--passes 不能与 --verify-only、--resume 或 --in-place 同时使用",
+        Locale::ZhTw => "--passes 不能與 --verify-only、--resume 或 --in-place 同時使用",
+        Locale::Ja => "--passes は --verify-only、--resume、--in-place と併用できません",
+        Locale::En => "--passes cannot be combined with --verify-only, --resume or --in-place",
+    }
+}
+
+pub fn cli_unknown_order(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "未知的读写顺序",
+        Locale::ZhTw => "未知的讀寫順序",
+        Locale::Ja => "不明なアクセス順序",
+        Locale::En => "Unknown block order",
+    }
+}
+
+pub fn cli_unknown_engine(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "未知的 I/O 引擎",
+        Locale::ZhTw => "未知的 I/O 引擎",
+        Locale::Ja => "不明な I/O エンジン",
+        Locale::En => "Unknown I/O engine",
+    }
+}
+
+pub fn cli_invalid_queue_depth(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "队列深度无效",
+        Locale::ZhTw => "佇列深度無效",
+        Locale::Ja => "無効なキュー深度",
+        Locale::En => "Invalid queue depth",
+    }
+}
+
+pub fn cli_invalid_verify_threads(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "校验线程数无效",
+        Locale::ZhTw => "校驗執行緒數無效",
+        Locale::Ja => "無効な検証スレッド数",
+        Locale::En => "Invalid verify thread count",
+    }
+}
+
+pub fn cli_invalid_segment_size(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "分段大小无效",
+        Locale::ZhTw => "分段大小無效",
+        Locale::Ja => "無効なセグメントサイズ",
+        Locale::En => "Invalid segment size",
+    }
+}
+
+pub fn cli_invalid_reserve(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "预留空间无效",
+        Locale::ZhTw => "預留空間無效",
+        Locale::Ja => "無効な予約領域サイズ",
+        Locale::En => "Invalid reserve size",
+    }
+}
+
+pub fn cli_invalid_seed(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "会话种子无效",
+        Locale::ZhTw => "工作階段種子無效",
+        Locale::Ja => "無効なセッションシード",
+        Locale::En => "Invalid session seed",
+    }
+}
+
+pub fn cli_target_missing(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "待校验的目标不存在",
+        Locale::ZhTw => "待校驗的目標不存在",
+        Locale::Ja => "検証対象が存在しません",
+        Locale::En => "Target to verify does not exist",
+    }
+}
+
+pub fn cli_rollback_nothing(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "没有需要回滚的日志",
+        Locale::ZhTw => "沒有需要回滾的日誌",
+        Locale::Ja => "ロールバックするジャーナルはありません",
+        Locale::En => "Nothing to roll back",
+    }
+}
+
+pub fn cli_rollback_failed(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "回滚失败",
+        Locale::ZhTw => "回滾失敗",
+        Locale::Ja => "ロールバックに失敗しました",
+        Locale::En => "Rollback failed",
+    }
+}
+
+pub fn cli_resume_failed(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "无法继续上次的测试",
+        Locale::ZhTw => "無法繼續上次的測試",
+        Locale::Ja => "前回のテストを再開できません",
+        Locale::En => "Unable to resume the previous run",
+    }
+}
+
+pub fn cli_target_not_dir(locale: Locale) -> &'static str {
+    match locale {
+        Locale::ZhCn => "多文件模式需要目标目录",
+        Locale::ZhTw => "多檔案模式需要目標目錄",
+        Locale::Ja => "マルチファイルモードには対象ディレクトリが必要です",
+        Locale::En => "Multi-file mode needs a target directory",
+    }
+}
+
 pub fn cli_target_is_dir(locale: Locale) -> &'static str {
     match locale {
         Locale::ZhCn => "目标路径是目录",
@@ -0,0 +1,560 @@
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::core_logic::{
+    BadRange, BadRangeKind, BitErrorStats, DriveHealthStatus, FailureBreakdown, MisplacedSector,
+    ScanStats,
+};
+
+use super::manifest::{Manifest, invalid};
+use super::order::AccessOrder;
+
+pub(super) const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckpointPhase {
+    Write,
+    Verify,
+}
+
+// How far one verify region got, with the counters it had gathered by then.
+#[derive(Debug, Clone)]
+pub(super) struct RegionProgress {
+    pub(super) start: u64,
+    pub(super) position: u64,
+    pub(super) end: u64,
+    pub(super) stats: ScanStats,
+}
+
+// Saved periodically while a run is in progress so a later run can carry on
+// from the last completed prefix of each phase instead of starting over. The
+// pattern, bytes written and block size are the same lines a manifest holds.
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    pub(super) target: String,
+    pub(super) manifest: Manifest,
+    pub(super) phase: CheckpointPhase,
+    pub(super) limit_bytes: u64,
+    pub(super) write_order: AccessOrder,
+    pub(super) verify_order: AccessOrder,
+    pub(super) write_speed_mbps: f64,
+    pub(super) regions: Vec<RegionProgress>,
+}
+
+impl Checkpoint {
+    pub fn phase(&self) -> CheckpointPhase {
+        self.phase
+    }
+
+    pub fn bytes_written(&self) -> u64 {
+        self.manifest.bytes_written
+    }
+
+    fn format(&self) -> String {
+        let mut text = self.manifest.format();
+        text.push_str(&format!(
+            "target={}\nphase={}\nlimit_bytes={}\nwrite_order={}\nverify_order={}\nwrite_speed_mbps={}\n",
+            self.target,
+            match self.phase {
+                CheckpointPhase::Write => "write",
+                CheckpointPhase::Verify => "verify",
+            },
+            self.limit_bytes,
+            self.write_order.name(),
+            self.verify_order.name(),
+            self.write_speed_mbps
+        ));
+        for (index, region) in self.regions.iter().enumerate() {
+            let stats = &region.stats;
+            text.push_str(&format!(
+                "region={},{},{},{},{},{},{},{},{}\n",
+                region.start,
+                region.position,
+                region.end,
+                stats.valid_bytes,
+                stats.corrupted_bytes,
+                stats.mismatch_blocks,
+                stats.read_error_blocks,
+                stats.unstable_blocks,
+                stats.highest_valid_end
+            ));
+            for range in &stats.bad_ranges {
+                let kind = match range.kind {
+                    BadRangeKind::Mismatch => "mismatch",
+                    BadRangeKind::ReadError => "read_error",
+                    BadRangeKind::WriteError => "write_error",
+                };
+                text.push_str(&format!(
+                    "bad_range={},{},{},{}\n",
+                    index, range.start, range.end, kind
+                ));
+            }
+            format_region_evidence(&mut text, index, stats);
+        }
+        text
+    }
+
+    fn parse(text: &str) -> io::Result<Self> {
+        let manifest = Manifest::parse(text)?;
+        let mut target = None;
+        let mut phase = None;
+        let mut limit_bytes = None;
+        let mut write_order = AccessOrder::Sequential;
+        let mut verify_order = AccessOrder::Sequential;
+        let mut write_speed_mbps = 0.0;
+        let mut regions: Vec<RegionProgress> = Vec::new();
+        for line in text.lines().map(str::trim) {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            match key {
+                "target" => target = Some(value.to_string()),
+                "phase" => {
+                    phase = match value {
+                        "write" => Some(CheckpointPhase::Write),
+                        "verify" => Some(CheckpointPhase::Verify),
+                        _ => None,
+                    }
+                }
+                "limit_bytes" => limit_bytes = value.parse().ok(),
+                "write_order" | "verify_order" => {
+                    let order = AccessOrder::from_name(value)
+                        .ok_or_else(|| invalid(format!("Unknown block order: {}", line)))?;
+                    if key == "write_order" {
+                        write_order = order;
+                    } else {
+                        verify_order = order;
+                    }
+                }
+                "write_speed_mbps" => write_speed_mbps = value.parse().unwrap_or(0.0),
+                "region" => regions.push(parse_region(value)?),
+                "bad_range" => {
+                    let (index, range) = parse_bad_range(value)?;
+                    region_stats(&mut regions, index, line)?
+                        .bad_ranges
+                        .push(range);
+                }
+                "region_counters" => {
+                    let [index, write_error, recovered] = parse_numbers(value)?;
+                    let stats = region_stats(&mut regions, index as usize, line)?;
+                    stats.write_error_blocks = write_error;
+                    stats.recovered_reads = recovered;
+                }
+                "region_status" => {
+                    let (index, status) = value
+                        .split_once(',')
+                        .and_then(|(index, name)| {
+                            Some((index.parse().ok()?, status_from_name(name)?))
+                        })
+                        .ok_or_else(|| invalid(format!("Malformed checkpoint entry: {}", line)))?;
+                    region_stats(&mut regions, index, line)?.record_status(status);
+                }
+                "misplaced" => {
+                    let [index, read_offset, written_offset] = parse_numbers(value)?;
+                    region_stats(&mut regions, index as usize, line)?.record_misplaced(
+                        MisplacedSector {
+                            read_offset,
+                            written_offset,
+                        },
+                    );
+                }
+                "failures" => {
+                    let [index, counts @ ..] = parse_numbers::<8>(value)?;
+                    region_stats(&mut regions, index as usize, line)?.failures =
+                        parse_failures(counts);
+                }
+                "bit_errors" => {
+                    let [index, counts @ ..] = parse_numbers::<27>(value)?;
+                    region_stats(&mut regions, index as usize, line)?.bit_errors =
+                        parse_bit_errors(counts);
+                }
+                "fingerprint" => {
+                    let [index, fingerprint, offset] = parse_numbers(value)?;
+                    region_stats(&mut regions, index as usize, line)?
+                        .fingerprints
+                        .restore(fingerprint, offset);
+                }
+                _ => {}
+            }
+        }
+        match (target, manifest.block_size, phase, limit_bytes) {
+            (Some(target), Some(_), Some(phase), Some(limit_bytes)) => Ok(Self {
+                target,
+                manifest,
+                phase,
+                limit_bytes,
+                write_order,
+                verify_order,
+                write_speed_mbps,
+                regions,
+            }),
+            _ => Err(invalid(
+                "Checkpoint is missing or has invalid fields.".to_string(),
+            )),
+        }
+    }
+}
+
+fn parse_numbers<const N: usize>(value: &str) -> io::Result<[u64; N]> {
+    let mut numbers = [0; N];
+    let mut fields = value.split(',');
+    for number in &mut numbers {
+        *number = fields
+            .next()
+            .and_then(|field| field.parse().ok())
+            .ok_or_else(|| invalid(format!("Malformed checkpoint entry: {}", value)))?;
+    }
+    Ok(numbers)
+}
+
+fn parse_region(value: &str) -> io::Result<RegionProgress> {
+    let [
+        start,
+        position,
+        end,
+        valid,
+        corrupted,
+        mismatch,
+        read_error,
+        unstable,
+        highest,
+    ] = parse_numbers(value)?;
+    if !(start <= position && position <= end) {
+        return Err(invalid(format!("Malformed checkpoint region: {}", value)));
+    }
+    let mut stats = ScanStats {
+        valid_bytes: valid,
+        corrupted_bytes: corrupted,
+        mismatch_blocks: mismatch,
+        read_error_blocks: read_error,
+        unstable_blocks: unstable,
+        highest_valid_end: highest,
+        ..ScanStats::default()
+    };
+    if unstable > 0 {
+        stats.record_status(DriveHealthStatus::Unstable);
+    }
+    Ok(RegionProgress {
+        start,
+        position,
+        end,
+        stats,
+    })
+}
+
+fn region_stats<'a>(
+    regions: &'a mut [RegionProgress],
+    index: usize,
+    line: &str,
+) -> io::Result<&'a mut ScanStats> {
+    regions
+        .get_mut(index)
+        .map(|region| &mut region.stats)
+        .ok_or_else(|| invalid(format!("Checkpoint entry for unknown region: {}", line)))
+}
+
+// What a region found beyond its counters: the status its samples pointed at
+// and the evidence behind it, so a resumed run reports the same diagnosis.
+fn format_region_evidence(text: &mut String, index: usize, stats: &ScanStats) {
+    if stats.write_error_blocks > 0 || stats.recovered_reads > 0 {
+        text.push_str(&format!(
+            "region_counters={},{},{}\n",
+            index, stats.write_error_blocks, stats.recovered_reads
+        ));
+    }
+    if let Some(status) = stats.sample_status {
+        text.push_str(&format!(
+            "region_status={},{}\n",
+            index,
+            status_name(status)
+        ));
+    }
+    for sector in &stats.misplaced_sectors {
+        text.push_str(&format!(
+            "misplaced={},{},{}\n",
+            index, sector.read_offset, sector.written_offset
+        ));
+    }
+    let failures = &stats.failures;
+    if *failures != FailureBreakdown::default() {
+        text.push_str(&format!(
+            "failures={},{},{},{},{},{},{},{}\n",
+            index,
+            failures.zero_fill,
+            failures.ones_fill,
+            failures.repeated_block,
+            failures.misplaced_data,
+            failures.stale_session,
+            failures.foreign_data,
+            failures.bit_corruption
+        ));
+    }
+    let bits = &stats.bit_errors;
+    if bits.flipped_bits > 0 {
+        let mut fields = vec![
+            index as u64,
+            bits.flipped_bits,
+            bits.zero_to_one,
+            bits.one_to_zero,
+            bits.sectors_with_errors,
+            bits.max_bits_per_sector,
+        ];
+        fields.extend(bits.sector_histogram);
+        for position in &bits.positions {
+            fields.extend([position.zero_to_one, position.one_to_zero]);
+        }
+        let fields: Vec<String> = fields.iter().map(u64::to_string).collect();
+        text.push_str(&format!("bit_errors={}\n", fields.join(",")));
+    }
+    for &(fingerprint, offset) in stats.fingerprints.entries() {
+        text.push_str(&format!(
+            "fingerprint={},{},{}\n",
+            index, fingerprint, offset
+        ));
+    }
+}
+
+fn parse_failures(counts: [u64; 7]) -> FailureBreakdown {
+    let [
+        zero_fill,
+        ones_fill,
+        repeated_block,
+        misplaced_data,
+        stale_session,
+        foreign_data,
+        bit_corruption,
+    ] = counts;
+    FailureBreakdown {
+        zero_fill,
+        ones_fill,
+        repeated_block,
+        misplaced_data,
+        stale_session,
+        foreign_data,
+        bit_corruption,
+    }
+}
+
+// The rates and stuck bits are derived when the report is built, so only the
+// raw counts are saved.
+fn parse_bit_errors(counts: [u64; 26]) -> BitErrorStats {
+    let mut bits = BitErrorStats {
+        flipped_bits: counts[0],
+        zero_to_one: counts[1],
+        one_to_zero: counts[2],
+        sectors_with_errors: counts[3],
+        max_bits_per_sector: counts[4],
+        ..BitErrorStats::default()
+    };
+    bits.sector_histogram.copy_from_slice(&counts[5..10]);
+    for (position, pair) in bits.positions.iter_mut().zip(counts[10..].chunks(2)) {
+        position.zero_to_one = pair[0];
+        position.one_to_zero = pair[1];
+    }
+    bits
+}
+
+fn status_name(status: DriveHealthStatus) -> &'static str {
+    match status {
+        DriveHealthStatus::Healthy => "healthy",
+        DriveHealthStatus::FakeCapacity => "fake_capacity",
+        DriveHealthStatus::PhysicalCorruption => "physical_corruption",
+        DriveHealthStatus::DataLoss => "data_loss",
+        DriveHealthStatus::StaleData => "stale_data",
+        DriveHealthStatus::Degraded => "degraded",
+        DriveHealthStatus::ReadOnly => "read_only",
+        DriveHealthStatus::Disconnected => "disconnected",
+        DriveHealthStatus::Unstable => "unstable",
+    }
+}
+
+fn status_from_name(name: &str) -> Option<DriveHealthStatus> {
+    [
+        DriveHealthStatus::Healthy,
+        DriveHealthStatus::FakeCapacity,
+        DriveHealthStatus::PhysicalCorruption,
+        DriveHealthStatus::DataLoss,
+        DriveHealthStatus::StaleData,
+        DriveHealthStatus::Degraded,
+        DriveHealthStatus::ReadOnly,
+        DriveHealthStatus::Disconnected,
+        DriveHealthStatus::Unstable,
+    ]
+    .into_iter()
+    .find(|&status| status_name(status) == name)
+}
+
+fn parse_bad_range(value: &str) -> io::Result<(usize, BadRange)> {
+    let (numbers, kind) = value
+        .rsplit_once(',')
+        .ok_or_else(|| invalid(format!("Malformed checkpoint entry: {}", value)))?;
+    let [index, start, end] = parse_numbers(numbers)?;
+    let kind = match kind {
+        "mismatch" => BadRangeKind::Mismatch,
+        "read_error" => BadRangeKind::ReadError,
+        "write_error" => BadRangeKind::WriteError,
+        _ => return Err(invalid(format!("Malformed checkpoint entry: {}", value))),
+    };
+    Ok((index as usize, BadRange { start, end, kind }))
+}
+
+// Next to the test file, or in the series directory. A device cannot hold its
+// own checkpoint, so that one goes to the working directory.
+pub(super) fn checkpoint_path(target: &Path, multi_file: bool, device_mode: bool) -> PathBuf {
+    if device_mode {
+        let name = target
+            .file_name()
+            .map_or("target".into(), |name| name.to_string_lossy());
+        return PathBuf::from(format!("truthbyte-{}.checkpoint", name));
+    }
+    if multi_file {
+        return target.join("tb.checkpoint");
+    }
+    let mut path = target.as_os_str().to_owned();
+    path.push(".checkpoint");
+    PathBuf::from(path)
+}
+
+// Written to a temporary name and renamed over the old one, so a crash while
+// saving leaves the previous checkpoint intact.
+pub(super) fn write_checkpoint(path: &Path, checkpoint: &Checkpoint) -> io::Result<()> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    fs::write(&temporary, checkpoint.format())?;
+    fs::rename(&temporary, path)
+}
+
+pub(super) fn read_checkpoint(path: &Path) -> io::Result<Option<Checkpoint>> {
+    match fs::read_to_string(path) {
+        Ok(text) => Checkpoint::parse(&text).map(Some),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Checkpoint, CheckpointPhase, RegionProgress};
+    use crate::core_logic::{
+        BadRange, BadRangeKind, DriveHealthStatus, FailureKind, MisplacedSector, Pattern,
+        PatternLayout, SPLITMIX, ScanStats,
+    };
+    use crate::io_controller::manifest::Manifest;
+    use crate::io_controller::order::AccessOrder;
+
+    fn checkpoint_with(stats: ScanStats) -> Checkpoint {
+        Checkpoint {
+            target: "/tmp/tb.dat".to_string(),
+            manifest: Manifest {
+                pattern: Pattern::new(7, PatternLayout::Plain, &SPLITMIX),
+                bytes_written: 8192,
+                block_size: Some(4096),
+            },
+            phase: CheckpointPhase::Verify,
+            limit_bytes: 8192,
+            write_order: AccessOrder::Sequential,
+            verify_order: AccessOrder::Sequential,
+            write_speed_mbps: 0.0,
+            regions: vec![RegionProgress {
+                start: 0,
+                position: 8192,
+                end: 8192,
+                stats,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_round_trips_fake_capacity_evidence() {
+        let mut stats = ScanStats {
+            mismatch_blocks: 1,
+            recovered_reads: 2,
+            ..ScanStats::default()
+        };
+        stats.record_failure(FailureKind::MisplacedData);
+        stats.record_misplaced(MisplacedSector {
+            read_offset: 4096,
+            written_offset: 0,
+        });
+        stats.bit_errors.record(4096, &[0x00; 4096], &[0x01; 4096]);
+        stats.fingerprints.restore(0xdead_beef, 4096);
+
+        let parsed = Checkpoint::parse(&checkpoint_with(stats.clone()).format()).unwrap();
+        let restored = &parsed.regions[0].stats;
+        assert_eq!(
+            restored.sample_status,
+            Some(DriveHealthStatus::FakeCapacity)
+        );
+        assert_eq!(restored.misplaced_sectors, stats.misplaced_sectors);
+        assert_eq!(restored.failures, stats.failures);
+        assert_eq!(restored.recovered_reads, 2);
+        assert_eq!(restored.bit_errors.flipped_bits, 4096);
+        assert_eq!(restored.bit_errors.positions, stats.bit_errors.positions);
+        assert_eq!(
+            restored.bit_errors.sector_histogram,
+            stats.bit_errors.sector_histogram
+        );
+        assert_eq!(restored.fingerprints.entries(), &[(0xdead_beef, 4096)]);
+    }
+
+    #[test]
+    fn test_checkpoint_round_trips_region_counters() {
+        let mut stats = ScanStats {
+            valid_bytes: 4096,
+            mismatch_blocks: 1,
+            corrupted_bytes: 12,
+            highest_valid_end: 8192,
+            ..ScanStats::default()
+        };
+        stats.bad_ranges.push(BadRange {
+            start: 100,
+            end: 112,
+            kind: BadRangeKind::Mismatch,
+        });
+        let checkpoint = Checkpoint {
+            target: "/tmp/tb.dat".to_string(),
+            manifest: Manifest {
+                pattern: Pattern::new(7, PatternLayout::Plain, &SPLITMIX),
+                bytes_written: 16384,
+                block_size: Some(4096),
+            },
+            phase: CheckpointPhase::Verify,
+            limit_bytes: 16384,
+            write_order: AccessOrder::Random,
+            verify_order: AccessOrder::Sequential,
+            write_speed_mbps: 12.5,
+            regions: vec![
+                RegionProgress {
+                    start: 0,
+                    position: 8192,
+                    end: 8192,
+                    stats,
+                },
+                RegionProgress {
+                    start: 8192,
+                    position: 8192,
+                    end: 16384,
+                    stats: ScanStats::default(),
+                },
+            ],
+        };
+
+        let parsed = Checkpoint::parse(&checkpoint.format()).unwrap();
+        assert_eq!(parsed.phase, CheckpointPhase::Verify);
+        assert_eq!(parsed.bytes_written(), 16384);
+        assert_eq!(parsed.write_order, AccessOrder::Random);
+        assert_eq!(parsed.write_speed_mbps, 12.5);
+        assert_eq!(parsed.regions.len(), 2);
+        let region = &parsed.regions[0];
+        assert_eq!((region.start, region.position, region.end), (0, 8192, 8192));
+        assert_eq!(region.stats.mismatch_blocks, 1);
+        assert_eq!(region.stats.corrupted_bytes, 12);
+        assert_eq!(
+            region.stats.bad_ranges,
+            checkpoint.regions[0].stats.bad_ranges
+        );
+        assert!(parsed.regions[1].stats.bad_ranges.is_empty());
+    }
+}
@@ -0,0 +1,206 @@
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use crate::core_logic::DriveGeometry;
+
+use super::DIRECT_IO_ALIGNMENT;
+
+const MIN_ALIGNMENT: u64 = 512;
+const MAX_ALIGNMENT: u64 = 1024 * 1024;
+
+// _IOR(0x12, 114, size_t) from <linux/fs.h>.
+#[cfg(target_os = "linux")]
+const BLKGETSIZE64: libc::Ioctl =
+    ((2 << 30) | (size_of::<usize>() << 16) | (0x12 << 8) | 114) as libc::Ioctl;
+
+pub fn is_block_device(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        std::fs::metadata(path).is_ok_and(|metadata| metadata.file_type().is_block_device())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        false
+    }
+}
+
+// The capacity the target advertises for itself: what the controller reports
+// for a block device, or the length of an image file standing in for one.
+pub(super) fn claimed_capacity(file: &File) -> io::Result<u64> {
+    let metadata = file.metadata()?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        if metadata.file_type().is_block_device() {
+            return block_device_size(file);
+        }
+    }
+    Ok(metadata.len())
+}
+
+// Space a run may fill on the filesystem holding `dir`: what an unprivileged
+// writer can still allocate, plus whatever `reused` already occupies since the
+// run replaces it. `None` where the platform offers no way to ask.
+pub(super) fn free_space(dir: &Path, reused: &Path) -> io::Result<Option<u64>> {
+    #[cfg(unix)]
+    {
+        use std::ffi::CString;
+        use std::os::unix::ffi::OsStrExt;
+        use std::os::unix::fs::MetadataExt;
+
+        let c_dir = CString::new(dir.as_os_str().as_bytes())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let mut statvfs = unsafe { std::mem::zeroed::<libc::statvfs>() };
+        if unsafe { libc::statvfs(c_dir.as_ptr(), &mut statvfs) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let available = statvfs.f_bavail as u64 * statvfs.f_frsize as u64;
+        // Allocated blocks rather than the length, so a sparse file left by an
+        // earlier probe is not counted as space it never took.
+        let reclaimable = std::fs::metadata(reused)
+            .ok()
+            .filter(|metadata| metadata.is_file())
+            .map_or(0, |metadata| metadata.blocks() * 512);
+        Ok(Some(available + reclaimable))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (dir, reused);
+        Ok(None)
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn block_device_size(file: &File) -> io::Result<u64> {
+    use std::os::fd::AsRawFd;
+
+    let mut size: u64 = 0;
+    let result = unsafe { libc::ioctl(file.as_raw_fd(), BLKGETSIZE64, &mut size as *mut u64) };
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(size)
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn block_device_size(mut file: &File) -> io::Result<u64> {
+    use std::io::{Seek, SeekFrom};
+
+    file.seek(SeekFrom::End(0))
+}
+
+// Queries whatever the platform exposes for an open target: sector sizes for a
+// block device, the direct I/O limits from statx on Linux 6.1+, and the block
+// size of the filesystem holding a test file.
+pub(super) fn detect_geometry(file: &File) -> DriveGeometry {
+    let mut geometry = DriveGeometry {
+        logical_sector_size: None,
+        physical_sector_size: None,
+        dio_mem_align: None,
+        dio_offset_align: None,
+        fs_block_size: None,
+        alignment: DIRECT_IO_ALIGNMENT as u64,
+    };
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::fd::AsRawFd;
+
+        let fd = file.as_raw_fd();
+        let mut logical: libc::c_int = 0;
+        if unsafe { libc::ioctl(fd, libc::BLKSSZGET, &mut logical as *mut libc::c_int) } == 0
+            && logical > 0
+        {
+            geometry.logical_sector_size = Some(logical as u32);
+        }
+        let mut physical: libc::c_uint = 0;
+        if unsafe { libc::ioctl(fd, libc::BLKPBSZGET, &mut physical as *mut libc::c_uint) } == 0
+            && physical > 0
+        {
+            geometry.physical_sector_size = Some(physical);
+        }
+
+        let mut statx = unsafe { std::mem::zeroed::<libc::statx>() };
+        let result = unsafe {
+            libc::statx(
+                fd,
+                c"".as_ptr(),
+                libc::AT_EMPTY_PATH,
+                libc::STATX_DIOALIGN,
+                &mut statx,
+            )
+        };
+        if result == 0
+            && statx.stx_mask & libc::STATX_DIOALIGN != 0
+            && statx.stx_dio_offset_align > 0
+        {
+            geometry.dio_mem_align = Some(statx.stx_dio_mem_align);
+            geometry.dio_offset_align = Some(statx.stx_dio_offset_align);
+        }
+    }
+    #[cfg(unix)]
+    {
+        use std::os::fd::AsRawFd;
+
+        let mut statvfs = unsafe { std::mem::zeroed::<libc::statvfs>() };
+        if unsafe { libc::fstatvfs(file.as_raw_fd(), &mut statvfs) } == 0 && statvfs.f_bsize > 0 {
+            geometry.fs_block_size = Some(statvfs.f_bsize as u64);
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = file;
+    }
+    geometry.alignment = choose_alignment(&geometry);
+    geometry
+}
+
+// The kernel's own direct I/O limits win, then the device's logical sector,
+// then the filesystem block size. Anything implausible falls back to 4096.
+fn choose_alignment(geometry: &DriveGeometry) -> u64 {
+    let offset = geometry
+        .dio_offset_align
+        .or(geometry.logical_sector_size)
+        .map(u64::from)
+        .or(geometry.fs_block_size)
+        .unwrap_or(DIRECT_IO_ALIGNMENT as u64);
+    let alignment = offset.max(geometry.dio_mem_align.map_or(0, u64::from));
+    if alignment.is_power_of_two() && (MIN_ALIGNMENT..=MAX_ALIGNMENT).contains(&alignment) {
+        alignment
+    } else {
+        DIRECT_IO_ALIGNMENT as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::choose_alignment;
+    use crate::core_logic::DriveGeometry;
+
+    #[test]
+    fn test_alignment_prefers_kernel_dio_limits() {
+        let mut geometry = DriveGeometry {
+            logical_sector_size: Some(512),
+            physical_sector_size: Some(4096),
+            dio_mem_align: None,
+            dio_offset_align: None,
+            fs_block_size: Some(4096),
+            alignment: 0,
+        };
+        assert_eq!(choose_alignment(&geometry), 512);
+
+        geometry.dio_mem_align = Some(4);
+        geometry.dio_offset_align = Some(2048);
+        assert_eq!(choose_alignment(&geometry), 2048);
+
+        geometry.dio_offset_align = Some(3000);
+        assert_eq!(choose_alignment(&geometry), 4096);
+
+        geometry.dio_offset_align = None;
+        geometry.logical_sector_size = None;
+        geometry.fs_block_size = Some(16384);
+        assert_eq!(choose_alignment(&geometry), 16384);
+    }
+}
@@ -1,19 +1,31 @@
-use std::alloc::{alloc, dealloc, Layout};
+use std::alloc::{Layout, alloc, dealloc};
 use std::fs::{File, OpenOptions};
 use std::io::{self, ErrorKind};
 use std::path::Path;
 use std::ptr::NonNull;
 
+use crate::core_logic::DriveHealthStatus;
+
 #[cfg(windows)]
 const FILE_FLAG_NO_BUFFERING: u32 = 0x20000000;
 #[cfg(windows)]
 const FILE_FLAG_WRITE_THROUGH: u32 = 0x80000000;
+#[cfg(windows)]
+const ERROR_WRITE_PROTECT: i32 = 19;
+#[cfg(windows)]
+const ERROR_NOT_READY: i32 = 21;
+#[cfg(windows)]
+const ERROR_DEV_NOT_EXIST: i32 = 55;
+#[cfg(windows)]
+const ERROR_NO_MEDIA_IN_DRIVE: i32 = 1112;
+#[cfg(windows)]
+const ERROR_DEVICE_NOT_CONNECTED: i32 = 1167;
 
 pub(super) fn align_up(value: usize, alignment: usize) -> usize {
     if alignment == 0 {
         return value;
     }
-    (value + alignment - 1) / alignment * alignment
+    value.div_ceil(alignment) * alignment
 }
 
 pub(super) fn align_down_u64(value: u64, alignment: u64) -> u64 {
@@ -23,46 +35,182 @@ pub(super) fn align_down_u64(value: u64, alignment: u64) -> u64 {
     value / alignment * alignment
 }
 
-pub(super) fn resolve_block_size(block_size: usize) -> io::Result<usize> {
+pub(super) fn resolve_block_size(block_size: usize, alignment: usize) -> io::Result<usize> {
     if block_size == 0 {
         return Err(io::Error::new(
             ErrorKind::InvalidInput,
             "Block size must be greater than zero.",
         ));
     }
-    Ok(align_up(block_size, super::DIRECT_IO_ALIGNMENT))
+    Ok(align_up(block_size, alignment))
 }
 
-pub(super) fn open_direct_write(path: &Path) -> io::Result<File> {
+// Devices and image files standing in for them are written in place; only a
+// test file is created and truncated.
+pub(super) fn open_write(path: &Path, create: bool, direct: bool) -> io::Result<File> {
     let mut options = OpenOptions::new();
-    options.write(true).create(true).truncate(true);
+    options.write(true).create(create).truncate(create);
+    if direct {
+        #[cfg(windows)]
+        {
+            use std::os::windows::fs::OpenOptionsExt;
+            options.custom_flags(FILE_FLAG_NO_BUFFERING | FILE_FLAG_WRITE_THROUGH);
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.custom_flags(libc::O_DIRECT);
+        }
+    }
+    options.open(path)
+}
+
+pub(super) fn open_read(path: &Path, direct: bool) -> io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.read(true);
+    if direct {
+        #[cfg(windows)]
+        {
+            use std::os::windows::fs::OpenOptionsExt;
+            options.custom_flags(FILE_FLAG_NO_BUFFERING);
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.custom_flags(libc::O_DIRECT);
+        }
+    }
+    options.open(path)
+}
+
+// tmpfs on older kernels, many FUSE filesystems and some network mounts refuse
+// O_DIRECT at open time with EINVAL.
+pub(super) fn is_direct_rejected(error: &io::Error) -> bool {
+    #[cfg(unix)]
+    {
+        error.raw_os_error() == Some(libc::EINVAL)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = error;
+        false
+    }
+}
+
+// Buffered fallback only: pushes a written range to the device and drops it
+// from the page cache, so the read-back cannot be served from memory.
+pub(super) fn flush_and_evict(file: &File, offset: u64, len: u64) -> io::Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::fd::AsRawFd;
+
+        let flags = libc::SYNC_FILE_RANGE_WAIT_BEFORE
+            | libc::SYNC_FILE_RANGE_WRITE
+            | libc::SYNC_FILE_RANGE_WAIT_AFTER;
+        let fd = file.as_raw_fd();
+        if unsafe { libc::sync_file_range(fd, offset as i64, len as i64, flags) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        fadvise_dontneed(file, offset, len)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (offset, len);
+        file.sync_data()
+    }
+}
+
+// Drops whatever the page cache still holds for the file before it is read
+// back. A length of zero covers the whole file.
+pub(super) fn evict_cache(file: &File) -> io::Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        file.sync_data()?;
+        fadvise_dontneed(file, 0, 0)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        file.sync_data()
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn fadvise_dontneed(file: &File, offset: u64, len: u64) -> io::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    let result = unsafe {
+        libc::posix_fadvise(
+            file.as_raw_fd(),
+            offset as libc::off_t,
+            len as libc::off_t,
+            libc::POSIX_FADV_DONTNEED,
+        )
+    };
+    if result != 0 {
+        return Err(io::Error::from_raw_os_error(result));
+    }
+    Ok(())
+}
+
+pub(super) fn read_at(file: &File, buffer: &mut [u8], offset: u64) -> io::Result<usize> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileExt;
+        file.read_at(buffer, offset)
+    }
     #[cfg(windows)]
     {
-        use std::os::windows::fs::OpenOptionsExt;
-        options.custom_flags(FILE_FLAG_NO_BUFFERING | FILE_FLAG_WRITE_THROUGH);
+        use std::os::windows::fs::FileExt;
+        file.seek_read(buffer, offset)
     }
+}
+
+pub(super) fn write_at(file: &File, buffer: &[u8], offset: u64) -> io::Result<usize> {
     #[cfg(unix)]
     {
-        use std::os::unix::fs::OpenOptionsExt;
-        options.custom_flags(libc::O_DIRECT);
+        use std::os::unix::fs::FileExt;
+        file.write_at(buffer, offset)
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::FileExt;
+        file.seek_write(buffer, offset)
     }
-    options.open(path)
 }
 
-pub(super) fn open_direct_read(path: &Path) -> io::Result<File> {
-    let mut options = OpenOptions::new();
-    options.read(true);
+// Errors that describe the device rather than a bad block: there is no point in
+// retrying or skipping ahead once the medium is write-protected or gone.
+pub fn device_error_status(error: &io::Error) -> Option<DriveHealthStatus> {
+    if error.kind() == ErrorKind::ReadOnlyFilesystem {
+        return Some(DriveHealthStatus::ReadOnly);
+    }
+    let code = error.raw_os_error()?;
     #[cfg(windows)]
     {
-        use std::os::windows::fs::OpenOptionsExt;
-        options.custom_flags(FILE_FLAG_NO_BUFFERING);
+        match code {
+            ERROR_WRITE_PROTECT => Some(DriveHealthStatus::ReadOnly),
+            ERROR_NOT_READY
+            | ERROR_DEV_NOT_EXIST
+            | ERROR_NO_MEDIA_IN_DRIVE
+            | ERROR_DEVICE_NOT_CONNECTED => Some(DriveHealthStatus::Disconnected),
+            _ => None,
+        }
     }
     #[cfg(unix)]
     {
-        use std::os::unix::fs::OpenOptionsExt;
-        options.custom_flags(libc::O_DIRECT);
+        match code {
+            libc::EROFS => Some(DriveHealthStatus::ReadOnly),
+            libc::ENODEV | libc::ENXIO => Some(DriveHealthStatus::Disconnected),
+            #[cfg(target_os = "linux")]
+            libc::ENOMEDIUM => Some(DriveHealthStatus::Disconnected),
+            _ => None,
+        }
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = code;
+        None
     }
-    options.open(path)
 }
 
 pub(super) struct AlignedBuffer {
@@ -77,9 +225,8 @@ impl AlignedBuffer {
             io::Error::new(ErrorKind::InvalidInput, "Invalid alignment for buffer.")
         })?;
         let ptr = unsafe { alloc(layout) };
-        let ptr = NonNull::new(ptr).ok_or_else(|| {
-            io::Error::new(ErrorKind::Other, "Failed to allocate aligned buffer.")
-        })?;
+        let ptr = NonNull::new(ptr)
+            .ok_or_else(|| io::Error::other("Failed to allocate aligned buffer."))?;
         Ok(Self {
             ptr,
             len,
@@ -90,8 +237,16 @@ impl AlignedBuffer {
     pub(super) fn as_mut_slice(&mut self) -> &mut [u8] {
         unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
     }
+
+    pub(super) fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.ptr.as_ptr()
+    }
 }
 
+// The buffer owns its allocation outright, so it can be handed to a worker
+// thread together with the block it describes.
+unsafe impl Send for AlignedBuffer {}
+
 impl Drop for AlignedBuffer {
     fn drop(&mut self) {
         let Ok(layout) = Layout::from_size_align(self.len, self.alignment) else {
@@ -0,0 +1,395 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::thread;
+use std::time::Instant;
+
+use crate::core_logic::{self, DiagnosisReport, DriveHealthStatus, ScanStats};
+
+use super::direct_io::{
+    AlignedBuffer, align_down_u64, device_error_status, flush_and_evict, resolve_block_size,
+};
+use super::probe::record_block_mismatch;
+use super::progress::{emit_error, emit_progress, percent_of, should_cancel, speed_mbps};
+use super::watchdog::IoWatchdog;
+
+const JOURNAL_MAGIC: &[u8; 8] = b"TBJRNL01";
+const JOURNAL_HEADER_LEN: usize = 32;
+
+impl super::DriveInspector {
+    pub fn run_in_place_phase(&self, limit_mb: u64, journal: &Path) -> io::Result<DiagnosisReport> {
+        self.run_in_place_phase_with_events(limit_mb, journal, None, None)
+    }
+
+    // Tests a device that holds data worth keeping, one block at a time: the
+    // block is saved to the journal, overwritten with the pattern, checked,
+    // then put back and checked again before the next block is touched.
+    pub fn run_in_place_phase_with_events(
+        &self,
+        limit_mb: u64,
+        journal: &Path,
+        cancel_flag: Option<Arc<AtomicBool>>,
+        sink: Option<&dyn super::EventSink>,
+    ) -> io::Result<DiagnosisReport> {
+        if !self.device_mode {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "In-place testing needs a block device or --device.",
+            ));
+        }
+        if journal.exists() {
+            return Err(io::Error::new(
+                ErrorKind::AlreadyExists,
+                format!(
+                    "Journal {} from an interrupted run exists; roll it back first.",
+                    journal.display()
+                ),
+            ));
+        }
+
+        let path = Path::new(&self.file_path);
+        let mut writer = self.open_target_write(path).map_err(|e| {
+            emit_error(sink, format!("Unable to open target for writing: {}", e));
+            e
+        })?;
+        let mut reader = self.open_target_read(path).map_err(|e| {
+            emit_error(sink, format!("Unable to open target for reading: {}", e));
+            e
+        })?;
+        let geometry = self.detect_geometry(&reader);
+        let alignment = geometry.alignment as usize;
+        let block_size = resolve_block_size(self.block_size, alignment)?;
+        let total_bytes = align_down_u64(self.limit_bytes(limit_mb)?, alignment as u64);
+        if total_bytes == 0 {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "Limit too small for direct I/O alignment.",
+            ));
+        }
+
+        let mut journal_file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(journal)?;
+        let mut original = AlignedBuffer::new(block_size, alignment)?;
+        let mut scratch = AlignedBuffer::new(block_size, alignment)?;
+        let mut stats = ScanStats {
+            geometry: Some(geometry),
+            buffered_io: self.is_buffered(),
+            ..ScanStats::default()
+        };
+
+        println!(
+            "[INFO] In-place test start. Target={}, Bytes={}, Journal={}, Session={:016x}",
+            self.file_path,
+            total_bytes,
+            journal.display(),
+            self.pattern.session_seed
+        );
+
+        let start_time = Instant::now();
+        let mut last_log_time = Instant::now();
+        let mut last_emit_time = Instant::now();
+        let mut offset = 0;
+        let mut untouched = false;
+        let watchdog = IoWatchdog::new(super::ProgressPhase::Verify, self.stall_threshold, sink);
+        let result = thread::scope(|scope| -> io::Result<()> {
+            let _watching = watchdog.watch(scope);
+            while offset < total_bytes && !should_cancel(&cancel_flag) {
+                let len = std::cmp::min(total_bytes - offset, block_size as u64) as usize;
+
+                // A block that cannot be read cannot be saved, so it is left alone.
+                let saved = &mut original.as_mut_slice()[..len];
+                let started = Instant::now();
+                let read = reader
+                    .seek(SeekFrom::Start(offset))
+                    .and_then(|_| reader.read_exact(saved));
+                watchdog.record_read(offset, started.elapsed());
+                if let Err(e) = read {
+                    println!("[ERROR] Read failure at offset {}: {}", offset, e);
+                    stats.record_read_error(offset, len as u64);
+                    offset += len as u64;
+                    continue;
+                }
+                write_journal(&mut journal_file, offset, saved)?;
+
+                let block = &mut scratch.as_mut_slice()[..len];
+                core_logic::fill_block(self.pattern, offset, block);
+                let started = Instant::now();
+                let written = self.write_block(&mut writer, offset, block);
+                watchdog.record_write(offset, started.elapsed());
+                if let Err(e) = written {
+                    // A protected or vanished device ends the run as a finding. Only
+                    // the protected one is known to be untouched.
+                    match device_error_status(&e) {
+                        Some(DriveHealthStatus::ReadOnly) => {
+                            untouched = true;
+                            return Err(e);
+                        }
+                        Some(_) => return Err(e),
+                        None => {}
+                    }
+                    emit_error(sink, format!("Write failure at offset {}: {}", offset, e));
+                    stats.record_write_error(offset, len as u64);
+                } else {
+                    let started = Instant::now();
+                    let readback = reader
+                        .seek(SeekFrom::Start(offset))
+                        .and_then(|_| reader.read_exact(block));
+                    watchdog.record_read(offset, started.elapsed());
+                    match readback {
+                        Err(e) => {
+                            println!("[ERROR] Read failure at offset {}: {}", offset, e);
+                            stats.record_read_error(offset, len as u64);
+                        }
+                        Ok(()) if core_logic::verify_block(self.pattern, offset, block).is_ok() => {
+                            stats.record_valid(offset, len as u64);
+                        }
+                        Ok(()) => {
+                            record_block_mismatch(self.pattern, offset, block, &[], &mut stats)
+                        }
+                    }
+                }
+
+                let saved = &mut original.as_mut_slice()[..len];
+                let restored = &mut scratch.as_mut_slice()[..len];
+                // The restore counts as one write, its check included.
+                let started = Instant::now();
+                let restore = self.restore_block(&mut writer, &mut reader, offset, saved, restored);
+                watchdog.record_write(offset, started.elapsed());
+                if let Err(e) = restore {
+                    let message = format!(
+                        "Restore of offset {} failed: {}. Journal kept at {}.",
+                        offset,
+                        e,
+                        journal.display()
+                    );
+                    emit_error(sink, message.clone());
+                    return Err(io::Error::new(e.kind(), message));
+                }
+                offset += len as u64;
+
+                if last_log_time.elapsed().as_secs() >= 2 {
+                    println!("[PROGRESS] Tested in place {} MB", offset / 1024 / 1024);
+                    last_log_time = Instant::now();
+                }
+                if last_emit_time.elapsed().as_millis() >= 500 {
+                    emit_progress(
+                        sink,
+                        super::ProgressUpdate {
+                            phase: super::ProgressPhase::Verify,
+                            percent: percent_of(offset, total_bytes),
+                            speed_mbps: speed_mbps(offset, start_time),
+                            bytes_written: offset,
+                            bytes_verified: offset,
+                            total_bytes,
+                        },
+                    );
+                    last_emit_time = Instant::now();
+                }
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            if untouched {
+                drop(journal_file);
+                fs::remove_file(journal)?;
+            }
+            return Err(e);
+        }
+        stats.write_latency = watchdog.write_latency();
+        stats.read_latency = watchdog.read_latency();
+
+        // Every block up to here has been restored and confirmed.
+        drop(journal_file);
+        fs::remove_file(journal)?;
+
+        let tested_bytes = offset;
+        if tested_bytes > 0 {
+            stats.verify_speed_mbps = Some(speed_mbps(tested_bytes, start_time));
+        }
+        let report = core_logic::generate_report(total_bytes, tested_bytes, &stats, self.pattern);
+        println!(
+            "[RESULT] In-place test complete: status={:?}, errors={}.",
+            report.status, report.error_count
+        );
+        emit_progress(
+            sink,
+            super::ProgressUpdate {
+                phase: super::ProgressPhase::Verify,
+                percent: percent_of(tested_bytes, total_bytes),
+                speed_mbps: speed_mbps(tested_bytes, start_time),
+                bytes_written: tested_bytes,
+                bytes_verified: tested_bytes,
+                total_bytes,
+            },
+        );
+        Ok(report)
+    }
+
+    // Puts back the block saved by an interrupted in-place run. Returns the
+    // offset restored, or `None` when there was nothing to undo.
+    pub fn roll_back_in_place(&self, journal: &Path) -> io::Result<Option<u64>> {
+        let contents = match fs::read(journal) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        // The device is only written once its journal entry is on disk, so a
+        // torn entry means that block was never touched.
+        let Some((offset, data)) = parse_journal(&contents) else {
+            println!("[INFO] Journal entry incomplete; the device was not modified.");
+            fs::remove_file(journal)?;
+            return Ok(None);
+        };
+
+        // Never create or truncate the target here: everything outside the
+        // journaled block still holds the user's data.
+        let path = Path::new(&self.file_path);
+        let mut writer = self.open_target_write_with(path, false)?;
+        let mut reader = self.open_target_read(path)?;
+        let alignment = self.detect_geometry(&reader).alignment as usize;
+        let mut saved = AlignedBuffer::new(data.len(), alignment)?;
+        let mut scratch = AlignedBuffer::new(data.len(), alignment)?;
+        saved.as_mut_slice().copy_from_slice(data);
+        self.restore_block(
+            &mut writer,
+            &mut reader,
+            offset,
+            saved.as_mut_slice(),
+            scratch.as_mut_slice(),
+        )?;
+        fs::remove_file(journal)?;
+        println!(
+            "[INFO] Restored {} bytes at offset {} from the journal.",
+            data.len(),
+            offset
+        );
+        Ok(Some(offset))
+    }
+
+    fn write_block(&self, writer: &mut File, offset: u64, data: &[u8]) -> io::Result<()> {
+        writer.seek(SeekFrom::Start(offset))?;
+        writer.write_all(data)?;
+        if self.is_buffered() {
+            flush_and_evict(writer, offset, data.len() as u64)
+        } else {
+            writer.sync_data()
+        }
+    }
+
+    fn restore_block(
+        &self,
+        writer: &mut File,
+        reader: &mut File,
+        offset: u64,
+        saved: &[u8],
+        scratch: &mut [u8],
+    ) -> io::Result<()> {
+        self.write_block(writer, offset, saved)?;
+        reader.seek(SeekFrom::Start(offset))?;
+        reader.read_exact(scratch)?;
+        if scratch != saved {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                "restored data reads back differently",
+            ));
+        }
+        Ok(())
+    }
+}
+
+// One entry, rewritten in place for every block: magic, offset, length, an
+// FNV-1a checksum over all three and the data, then the data itself.
+fn write_journal(journal: &mut File, offset: u64, data: &[u8]) -> io::Result<()> {
+    let mut header = [0u8; JOURNAL_HEADER_LEN];
+    header[..8].copy_from_slice(JOURNAL_MAGIC);
+    header[8..16].copy_from_slice(&offset.to_le_bytes());
+    header[16..24].copy_from_slice(&(data.len() as u64).to_le_bytes());
+    let checksum = journal_checksum(&header[..24], data);
+    header[24..].copy_from_slice(&checksum.to_le_bytes());
+
+    journal.seek(SeekFrom::Start(0))?;
+    journal.write_all(&header)?;
+    journal.write_all(data)?;
+    journal.set_len((JOURNAL_HEADER_LEN + data.len()) as u64)?;
+    journal.sync_data()
+}
+
+fn parse_journal(contents: &[u8]) -> Option<(u64, &[u8])> {
+    let header = contents.get(..JOURNAL_HEADER_LEN)?;
+    if &header[..8] != JOURNAL_MAGIC {
+        return None;
+    }
+    let offset = u64::from_le_bytes(header[8..16].try_into().ok()?);
+    let len = u64::from_le_bytes(header[16..24].try_into().ok()?) as usize;
+    let checksum = u64::from_le_bytes(header[24..].try_into().ok()?);
+    let data = contents.get(JOURNAL_HEADER_LEN..)?;
+    (data.len() == len && journal_checksum(&header[..24], data) == checksum)
+        .then_some((offset, data))
+}
+
+fn journal_checksum(header: &[u8], data: &[u8]) -> u64 {
+    header
+        .iter()
+        .chain(data)
+        .fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{JOURNAL_HEADER_LEN, parse_journal, write_journal};
+    use crate::io_controller::DriveInspector;
+
+    #[test]
+    fn test_torn_journal_entries_are_rejected() {
+        let path = std::env::temp_dir().join(format!("truthbyte-journal-{}", std::process::id()));
+        let mut file = std::fs::File::create(&path).unwrap();
+        write_journal(&mut file, 8192, &[0xA5; 4096]).unwrap();
+        write_journal(&mut file, 4096, &[0x5A; 512]).unwrap();
+        drop(file);
+        let mut contents = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(contents.len(), JOURNAL_HEADER_LEN + 512);
+        assert_eq!(parse_journal(&contents), Some((4096, &[0x5A; 512][..])));
+
+        contents[JOURNAL_HEADER_LEN + 100] ^= 1;
+        assert_eq!(parse_journal(&contents), None);
+        assert_eq!(parse_journal(&contents[..JOURNAL_HEADER_LEN + 10]), None);
+    }
+
+    #[test]
+    fn test_roll_back_keeps_data_outside_the_journal() {
+        let dir = std::env::temp_dir();
+        let target = dir.join(format!("truthbyte-rollback-{}", std::process::id()));
+        let journal = dir.join(format!("truthbyte-rollback-{}.journal", std::process::id()));
+        std::fs::write(&target, vec![0x11; 16384]).unwrap();
+        let mut file = std::fs::File::create(&journal).unwrap();
+        write_journal(&mut file, 4096, &[0xA5; 4096]).unwrap();
+        drop(file);
+
+        let inspector = DriveInspector::new(target.to_str().unwrap());
+        assert_eq!(inspector.roll_back_in_place(&journal).unwrap(), Some(4096));
+        let contents = std::fs::read(&target).unwrap();
+        assert_eq!(contents.len(), 16384);
+        assert!(contents[..4096].iter().all(|&byte| byte == 0x11));
+        assert!(contents[4096..8192].iter().all(|&byte| byte == 0xA5));
+        assert!(contents[8192..].iter().all(|&byte| byte == 0x11));
+        assert!(!journal.exists());
+
+        // A missing target is an error, not a new file, and the journal stays.
+        std::fs::remove_file(&target).unwrap();
+        let mut file = std::fs::File::create(&journal).unwrap();
+        write_journal(&mut file, 4096, &[0xA5; 4096]).unwrap();
+        drop(file);
+        assert!(inspector.roll_back_in_place(&journal).is_err());
+        assert!(!target.exists());
+        assert!(journal.exists());
+        std::fs::remove_file(&journal).unwrap();
+    }
+}
@@ -0,0 +1,135 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use crate::core_logic::{Pattern, PatternLayout, builtin_generator};
+
+// Everything a later verify needs to regenerate what the write phase put on
+// the target, kept as plain key=value lines next to it. Each block's data is
+// seeded from where the block starts, so the block size is part of it;
+// manifests from older versions lack it.
+#[derive(Debug, Clone, Copy)]
+pub struct Manifest {
+    pub pattern: Pattern,
+    pub bytes_written: u64,
+    pub block_size: Option<usize>,
+}
+
+impl Manifest {
+    pub(super) fn format(&self) -> String {
+        let mut text = format!(
+            "session_seed={:016x}\nlayout={}\ngenerator={}\nbytes_written={}\n",
+            self.pattern.session_seed,
+            self.pattern.layout.name(),
+            self.pattern.generator.name(),
+            self.bytes_written
+        );
+        if let Some(block_size) = self.block_size {
+            text.push_str(&format!("block_size={}\n", block_size));
+        }
+        text
+    }
+
+    pub(super) fn parse(text: &str) -> io::Result<Self> {
+        let mut session_seed = None;
+        let mut layout = None;
+        let mut generator = None;
+        let mut bytes_written = None;
+        let mut block_size = None;
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(invalid(format!("Malformed manifest line: {}", line)));
+            };
+            match key {
+                "session_seed" => session_seed = u64::from_str_radix(value, 16).ok(),
+                "layout" => layout = PatternLayout::from_name(value),
+                "generator" => generator = builtin_generator(value),
+                "bytes_written" => bytes_written = value.parse().ok(),
+                "block_size" => {
+                    block_size =
+                        Some(
+                            value.parse().ok().filter(|&size| size > 0).ok_or_else(|| {
+                                invalid(format!("Malformed manifest line: {}", line))
+                            })?,
+                        )
+                }
+                // Keys from newer versions are skipped rather than rejected.
+                _ => {}
+            }
+        }
+        match (session_seed, layout, generator, bytes_written) {
+            (Some(session_seed), Some(layout), Some(generator), Some(bytes_written)) => Ok(Self {
+                pattern: Pattern::new(session_seed, layout, generator),
+                bytes_written,
+                block_size,
+            }),
+            _ => Err(invalid(
+                "Manifest is missing or has invalid fields.".to_string(),
+            )),
+        }
+    }
+}
+
+pub(super) fn invalid(message: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+// A test file keeps its manifest alongside as `<file>.manifest`; a multi-file
+// series keeps one in its directory.
+pub(super) fn manifest_path(target: &Path, multi_file: bool) -> PathBuf {
+    if multi_file {
+        return target.join("tb.manifest");
+    }
+    let mut path = OsString::from(target.as_os_str());
+    path.push(".manifest");
+    PathBuf::from(path)
+}
+
+pub(super) fn write_manifest(path: &Path, manifest: &Manifest) -> io::Result<()> {
+    fs::write(path, manifest.format())
+}
+
+pub(super) fn read_manifest(path: &Path) -> io::Result<Option<Manifest>> {
+    match fs::read_to_string(path) {
+        Ok(text) => Manifest::parse(&text).map(Some),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+pub(super) fn remove_if_present(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Manifest;
+    use crate::core_logic::{CHACHA20, Pattern, PatternLayout};
+
+    #[test]
+    fn test_manifest_round_trips() {
+        let manifest = Manifest {
+            pattern: Pattern::new(
+                0x0123_4567_89ab_cdef,
+                PatternLayout::SectorHeaders,
+                &CHACHA20,
+            ),
+            bytes_written: 64 * 1024 * 1024,
+            block_size: Some(1024 * 1024),
+        };
+        let parsed = Manifest::parse(&manifest.format()).unwrap();
+        assert_eq!(parsed.pattern.session_seed, 0x0123_4567_89ab_cdef);
+        assert_eq!(parsed.pattern.layout, PatternLayout::SectorHeaders);
+        assert_eq!(parsed.pattern.generator.name(), "chacha20");
+        assert_eq!(parsed.bytes_written, 64 * 1024 * 1024);
+        assert_eq!(parsed.block_size, Some(1024 * 1024));
+        let older = "session_seed=1\nlayout=plain\ngenerator=splitmix\nbytes_written=8\n";
+        assert_eq!(Manifest::parse(older).unwrap().block_size, None);
+
+        assert!(Manifest::parse("session_seed=zz\nlayout=plain").is_err());
+    }
+}
@@ -1,18 +1,63 @@
+mod checkpoint;
+mod device;
 mod direct_io;
-mod progress;
+mod in_place;
+mod manifest;
+mod order;
+mod pipeline;
 mod probe;
+mod progress;
+mod queue;
+mod segments;
+mod throughput;
+#[cfg(target_os = "linux")]
+mod uring;
 mod verify;
+mod watchdog;
 mod write;
 
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
 use crate::config::AppConfig;
+use crate::core_logic::{self, DriveGeometry, Pattern};
 
-pub use progress::{EventSink, ProgressPhase, ProgressUpdate};
+pub use checkpoint::{Checkpoint, CheckpointPhase};
+pub use device::is_block_device;
+pub use direct_io::device_error_status;
+pub use manifest::Manifest;
+pub use order::AccessOrder;
+pub use progress::{EventSink, ProgressPhase, ProgressUpdate, StallEvent};
+pub use queue::IoEngine;
+pub use segments::segment_path;
+pub use write::WriteSummary;
 
+// Used when the target does not report its own direct I/O constraints.
 const DIRECT_IO_ALIGNMENT: usize = 4096;
 
 pub struct DriveInspector {
     file_path: String,
     block_size: usize,
+    // The block size data already on the target was written with, when a
+    // manifest says so. A verify has to read it back in the same blocks.
+    written_block_size: Option<usize>,
+    pattern: Pattern,
+    io_engine: IoEngine,
+    queue_depth: usize,
+    worker_threads: usize,
+    verify_threads: usize,
+    device_mode: bool,
+    multi_file: bool,
+    segment_bytes: u64,
+    reserve_bytes: u64,
+    write_order: AccessOrder,
+    verify_order: AccessOrder,
+    stall_threshold: Duration,
+    curve_region_bytes: u64,
+    buffered_io: AtomicBool,
 }
 
 impl DriveInspector {
@@ -24,6 +69,286 @@ impl DriveInspector {
         Self {
             file_path: path.to_string(),
             block_size: config.block_size,
+            written_block_size: None,
+            pattern: Pattern::new(
+                core_logic::generate_session_seed(),
+                config.pattern_layout,
+                config.pattern_generator,
+            ),
+            io_engine: config.io_engine,
+            queue_depth: config.queue_depth,
+            worker_threads: config.worker_threads.max(1),
+            verify_threads: config.verify_threads.max(1),
+            device_mode: config.raw_device || device::is_block_device(Path::new(path)),
+            multi_file: config.multi_file,
+            segment_bytes: config.segment_mb.max(1) * 1024 * 1024,
+            reserve_bytes: config.reserve_mb * 1024 * 1024,
+            write_order: config.write_order,
+            verify_order: config.verify_order,
+            stall_threshold: Duration::from_secs(config.stall_secs),
+            curve_region_bytes: config.curve_region_mb.max(1) * 1024 * 1024,
+            buffered_io: AtomicBool::new(false),
+        }
+    }
+
+    pub fn with_session_seed(mut self, session_seed: u64) -> Self {
+        self.pattern.session_seed = session_seed;
+        self
+    }
+
+    pub fn session_seed(&self) -> u64 {
+        self.pattern.session_seed
+    }
+
+    pub fn pattern(&self) -> Pattern {
+        self.pattern
+    }
+
+    pub fn with_pattern(mut self, pattern: Pattern) -> Self {
+        self.pattern = pattern;
+        self
+    }
+
+    // What the last write phase left behind. A raw device has nowhere to keep
+    // a manifest, so it never has one.
+    pub fn read_manifest(&self) -> io::Result<Option<Manifest>> {
+        match self.manifest_path() {
+            Some(path) => manifest::read_manifest(&path),
+            None => Ok(None),
+        }
+    }
+
+    // Takes on the pattern and block size the manifest was written with.
+    pub fn with_manifest(mut self, manifest: &Manifest) -> Self {
+        self.pattern = manifest.pattern;
+        if let Some(block_size) = manifest.block_size {
+            self.block_size = block_size;
+            self.written_block_size = Some(block_size);
         }
+        self
+    }
+
+    // The progress an earlier run saved before it stopped, if any.
+    pub fn read_checkpoint(&self) -> io::Result<Option<Checkpoint>> {
+        checkpoint::read_checkpoint(&self.checkpoint_path())
+    }
+
+    // Takes on the pattern, block size and orders of the run that saved
+    // `checkpoint`, so resuming it regenerates exactly the same data.
+    pub fn with_checkpoint(mut self, checkpoint: &Checkpoint) -> io::Result<Self> {
+        if checkpoint.target != self.file_path {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Checkpoint belongs to {}.", checkpoint.target),
+            ));
+        }
+        self.pattern = checkpoint.manifest.pattern;
+        if let Some(block_size) = checkpoint.manifest.block_size {
+            self.block_size = block_size;
+        }
+        self.write_order = checkpoint.write_order;
+        self.verify_order = checkpoint.verify_order;
+        Ok(self)
+    }
+
+    // Bytes available for a verify without a manifest: the claimed capacity of
+    // a device, otherwise the length of the test file or series, rounded down
+    // to the direct I/O alignment.
+    pub fn target_length(&self) -> io::Result<u64> {
+        let path = Path::new(&self.file_path);
+        let (first, length) = if self.multi_file {
+            let segments = segments::discover_segments(path, u64::MAX)?;
+            (
+                segments::segment_path(path, 0),
+                segments.iter().map(|segment| segment.len).sum(),
+            )
+        } else if self.device_mode {
+            let file = File::open(path)?;
+            (path.to_path_buf(), device::claimed_capacity(&file)?)
+        } else {
+            (path.to_path_buf(), std::fs::metadata(path)?.len())
+        };
+        let alignment = device::detect_geometry(&File::open(first)?).alignment;
+        Ok(direct_io::align_down_u64(length, alignment))
+    }
+
+    // Upper bound for the run in bytes, before alignment. A device is never
+    // written past the capacity it claims. Without a limit, a test file is
+    // sized to the free space of its volume less the reserve, which is left
+    // for the filesystem's own bookkeeping.
+    fn limit_bytes(&self, limit_mb: u64) -> io::Result<u64> {
+        let limit = if limit_mb == 0 {
+            u64::MAX
+        } else {
+            limit_mb * 1024 * 1024
+        };
+        if self.device_mode {
+            let capacity = device::claimed_capacity(&File::open(&self.file_path)?)?;
+            println!("[INFO] Device mode: claimed capacity {} bytes.", capacity);
+            return Ok(limit.min(capacity));
+        }
+        if limit_mb != 0 {
+            return Ok(limit);
+        }
+
+        let path = Path::new(&self.file_path);
+        let dir = if self.multi_file {
+            path
+        } else {
+            match path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => Path::new("."),
+            }
+        };
+        let Some(free) = device::free_space(dir, path)? else {
+            return Ok(u64::MAX);
+        };
+        if free <= self.reserve_bytes {
+            return Err(io::Error::new(
+                io::ErrorKind::StorageFull,
+                format!(
+                    "Only {} bytes free, within the {} byte reserve.",
+                    free, self.reserve_bytes
+                ),
+            ));
+        }
+        let limit = free - self.reserve_bytes;
+        println!(
+            "[INFO] Auto size: {} bytes free, testing {} bytes.",
+            free, limit
+        );
+        Ok(limit)
+    }
+
+    fn open_target_write(&self, path: &Path) -> io::Result<File> {
+        self.open_target_write_with(path, !self.device_mode)
+    }
+
+    // When the filesystem refuses O_DIRECT the run carries on with buffered
+    // I/O, and every later open follows suit so the two never mix on one file.
+    // Without `create` an existing file is opened as is, for resuming into it.
+    fn open_target_write_with(&self, path: &Path, create: bool) -> io::Result<File> {
+        if !self.is_buffered() {
+            match direct_io::open_write(path, create, true) {
+                Err(e) if direct_io::is_direct_rejected(&e) => self.fall_back_to_buffered(&e),
+                result => return result,
+            }
+        }
+        direct_io::open_write(path, create, false)
+    }
+
+    fn open_target_read(&self, path: &Path) -> io::Result<File> {
+        if !self.is_buffered() {
+            match direct_io::open_read(path, true) {
+                Err(e) if direct_io::is_direct_rejected(&e) => self.fall_back_to_buffered(&e),
+                result => return result,
+            }
+        }
+        direct_io::open_read(path, false)
+    }
+
+    fn fall_back_to_buffered(&self, error: &io::Error) {
+        if !self.buffered_io.swap(true, Ordering::Relaxed) {
+            println!(
+                "[INFO] Direct I/O rejected ({}); falling back to buffered I/O with cache eviction.",
+                error
+            );
+        }
+    }
+
+    fn manifest_path(&self) -> Option<PathBuf> {
+        (!self.device_mode)
+            .then(|| manifest::manifest_path(Path::new(&self.file_path), self.multi_file))
+    }
+
+    fn checkpoint_path(&self) -> PathBuf {
+        checkpoint::checkpoint_path(
+            Path::new(&self.file_path),
+            self.multi_file,
+            self.device_mode,
+        )
+    }
+
+    // Dropped before the target is rewritten, so an interrupted run cannot
+    // leave a manifest or checkpoint describing data that is no longer there.
+    fn forget_previous_run(&self) -> io::Result<()> {
+        if let Some(path) = self.manifest_path() {
+            manifest::remove_if_present(&path)?;
+        }
+        manifest::remove_if_present(&self.checkpoint_path())
+    }
+
+    fn new_checkpoint(
+        &self,
+        phase: CheckpointPhase,
+        limit_bytes: u64,
+        bytes_written: u64,
+        write_speed_mbps: f64,
+    ) -> Checkpoint {
+        Checkpoint {
+            target: self.file_path.clone(),
+            manifest: Manifest {
+                pattern: self.pattern,
+                bytes_written,
+                block_size: Some(self.block_size),
+            },
+            phase,
+            limit_bytes,
+            write_order: self.write_order,
+            verify_order: self.verify_order,
+            write_speed_mbps,
+            regions: Vec::new(),
+        }
+    }
+
+    // A checkpoint that cannot be saved costs only the ability to resume, so
+    // the run goes on.
+    fn save_checkpoint(&self, checkpoint: &Checkpoint) {
+        let path = self.checkpoint_path();
+        if let Err(e) = checkpoint::write_checkpoint(&path, checkpoint) {
+            println!(
+                "[WARN] Unable to write checkpoint {}: {}",
+                path.display(),
+                e
+            );
+        }
+    }
+
+    fn remove_checkpoint(&self) {
+        let path = self.checkpoint_path();
+        if let Err(e) = manifest::remove_if_present(&path) {
+            println!(
+                "[WARN] Unable to remove checkpoint {}: {}",
+                path.display(),
+                e
+            );
+        }
+    }
+
+    fn save_manifest(&self, bytes_written: u64, block_size: usize) {
+        let Some(path) = self.manifest_path() else {
+            return;
+        };
+        let manifest = Manifest {
+            pattern: self.pattern,
+            bytes_written,
+            block_size: Some(block_size),
+        };
+        if let Err(e) = manifest::write_manifest(&path, &manifest) {
+            println!("[WARN] Unable to write manifest {}: {}", path.display(), e);
+        }
+    }
+
+    fn is_buffered(&self) -> bool {
+        self.buffered_io.load(Ordering::Relaxed)
+    }
+
+    fn detect_geometry(&self, file: &File) -> DriveGeometry {
+        let geometry = device::detect_geometry(file);
+        println!(
+            "[INFO] Direct I/O alignment: {} bytes (logical={:?}, physical={:?}).",
+            geometry.alignment, geometry.logical_sector_size, geometry.physical_sector_size
+        );
+        geometry
     }
 }
@@ -0,0 +1,173 @@
+use std::ops::Range;
+
+// Lanes for strided order: every 64th block first, then the ones after them.
+const STRIDE_LANES: u64 = 64;
+const FEISTEL_ROUNDS: u64 = 4;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AccessOrder {
+    #[default]
+    Sequential,
+    Reverse,
+    Random,
+    Strided,
+}
+
+impl AccessOrder {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "sequential" => Some(AccessOrder::Sequential),
+            "reverse" => Some(AccessOrder::Reverse),
+            "random" => Some(AccessOrder::Random),
+            "strided" => Some(AccessOrder::Strided),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AccessOrder::Sequential => "sequential",
+            AccessOrder::Reverse => "reverse",
+            AccessOrder::Random => "random",
+            AccessOrder::Strided => "strided",
+        }
+    }
+}
+
+// Walks the blocks of a range in the chosen order, yielding each block's
+// offset and length. Only the last block of the range can be short. The order
+// is a pure function of its inputs, so a resumed run can skip the blocks the
+// previous one completed and carry on with the same sequence.
+pub(super) struct BlockOrder {
+    order: AccessOrder,
+    range: Range<u64>,
+    block_size: u64,
+    count: u64,
+    next_index: u64,
+    seed: u64,
+    half_bits: u32,
+}
+
+impl BlockOrder {
+    pub(super) fn new(order: AccessOrder, range: Range<u64>, block_size: u64, seed: u64) -> Self {
+        let count = (range.end - range.start).div_ceil(block_size);
+        // The permutation runs over the smallest even power of two holding
+        // every block; indices that land outside are walked again.
+        let bits = (u64::BITS - count.saturating_sub(1).leading_zeros()).max(2);
+        Self {
+            order,
+            seed: mix(seed ^ range.start),
+            range,
+            block_size,
+            count,
+            next_index: 0,
+            half_bits: bits.div_ceil(2),
+        }
+    }
+
+    pub(super) fn skip_blocks(mut self, blocks: u64) -> Self {
+        self.next_index = blocks.min(self.count);
+        self
+    }
+
+    fn block_at(&self, index: u64) -> u64 {
+        match self.order {
+            AccessOrder::Sequential => index,
+            AccessOrder::Reverse => self.count - 1 - index,
+            AccessOrder::Strided => {
+                let lanes = STRIDE_LANES.min(self.count);
+                let per_lane = self.count / lanes;
+                let longer = self.count % lanes;
+                let (lane, step) = if index < longer * (per_lane + 1) {
+                    (index / (per_lane + 1), index % (per_lane + 1))
+                } else {
+                    let rest = index - longer * (per_lane + 1);
+                    (longer + rest / per_lane, rest % per_lane)
+                };
+                lane + step * lanes
+            }
+            AccessOrder::Random => {
+                let mut block = self.permute(index);
+                while block >= self.count {
+                    block = self.permute(block);
+                }
+                block
+            }
+        }
+    }
+
+    // A small Feistel network: a bijection on [0, 2^(2 * half_bits)).
+    fn permute(&self, value: u64) -> u64 {
+        let mask = (1u64 << self.half_bits) - 1;
+        let mut left = value >> self.half_bits;
+        let mut right = value & mask;
+        for round in 0..FEISTEL_ROUNDS {
+            let next = left ^ (mix(self.seed ^ (round << 56) ^ right) & mask);
+            left = right;
+            right = next;
+        }
+        (left << self.half_bits) | right
+    }
+}
+
+impl Iterator for BlockOrder {
+    type Item = (u64, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_index >= self.count {
+            return None;
+        }
+        let offset = self.range.start + self.block_at(self.next_index) * self.block_size;
+        self.next_index += 1;
+        let len = (self.range.end - offset).min(self.block_size) as usize;
+        Some((offset, len))
+    }
+}
+
+fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AccessOrder, BlockOrder};
+
+    #[test]
+    fn test_every_order_visits_each_block_once() {
+        let range = 4096..4096 + 1000 * 512 + 100;
+        for order in [
+            AccessOrder::Sequential,
+            AccessOrder::Reverse,
+            AccessOrder::Random,
+            AccessOrder::Strided,
+        ] {
+            let mut blocks: Vec<(u64, usize)> =
+                BlockOrder::new(order, range.clone(), 512, 7).collect();
+            let resumed: Vec<(u64, usize)> = BlockOrder::new(order, range.clone(), 512, 7)
+                .skip_blocks(400)
+                .collect();
+            assert_eq!(resumed, blocks[400..], "{:?}", order);
+            blocks.sort_unstable();
+            let expected: Vec<(u64, usize)> =
+                BlockOrder::new(AccessOrder::Sequential, range.clone(), 512, 0).collect();
+            assert_eq!(blocks, expected, "{:?}", order);
+        }
+
+        let reverse: Vec<u64> = BlockOrder::new(AccessOrder::Reverse, 0..4096, 1024, 0)
+            .map(|(offset, _)| offset)
+            .collect();
+        assert_eq!(reverse, vec![3072, 2048, 1024, 0]);
+        let first: Vec<u64> = BlockOrder::new(AccessOrder::Random, 0..1 << 20, 512, 1)
+            .take(8)
+            .map(|(offset, _)| offset)
+            .collect();
+        let other: Vec<u64> = BlockOrder::new(AccessOrder::Random, 0..1 << 20, 512, 2)
+            .take(8)
+            .map(|(offset, _)| offset)
+            .collect();
+        assert_ne!(first, other);
+    }
+}
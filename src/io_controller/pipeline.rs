@@ -0,0 +1,201 @@
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::Scope;
+
+use crate::core_logic::{self, Pattern};
+
+use super::direct_io::AlignedBuffer;
+use super::order::BlockOrder;
+use super::queue::{BlockQueue, Completed};
+
+pub(super) struct Filled {
+    pub(super) offset: u64,
+    pub(super) len: usize,
+    pub(super) buffer: AlignedBuffer,
+}
+
+// Pattern generation runs on worker threads so the writer only waits on the
+// device. Blocks may finish filling in any order; `next` hands them back in the
+// order they were submitted.
+pub(super) struct FillPipeline {
+    jobs: Sender<Filled>,
+    done: Receiver<Filled>,
+    order: VecDeque<u64>,
+    ready: HashMap<u64, Filled>,
+}
+
+impl FillPipeline {
+    pub(super) fn spawn<'scope>(
+        scope: &'scope Scope<'scope, '_>,
+        pattern: Pattern,
+        threads: usize,
+    ) -> Self {
+        let (jobs, job_rx) = mpsc::channel::<Filled>();
+        let (done_tx, done) = mpsc::channel();
+        let job_rx = Arc::new(Mutex::new(job_rx));
+        for _ in 0..threads.max(1) {
+            let job_rx = Arc::clone(&job_rx);
+            let done_tx = done_tx.clone();
+            scope.spawn(move || {
+                loop {
+                    let job = match job_rx.lock() {
+                        Ok(receiver) => receiver.recv(),
+                        Err(_) => break,
+                    };
+                    let Ok(mut job) = job else {
+                        break;
+                    };
+                    core_logic::fill_block(
+                        pattern,
+                        job.offset,
+                        &mut job.buffer.as_mut_slice()[..job.len],
+                    );
+                    if done_tx.send(job).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        Self {
+            jobs,
+            done,
+            order: VecDeque::new(),
+            ready: HashMap::new(),
+        }
+    }
+
+    pub(super) fn pending(&self) -> usize {
+        self.order.len()
+    }
+
+    pub(super) fn submit(
+        &mut self,
+        offset: u64,
+        len: usize,
+        buffer: AlignedBuffer,
+    ) -> io::Result<()> {
+        self.jobs
+            .send(Filled {
+                offset,
+                len,
+                buffer,
+            })
+            .map_err(|_| io::Error::other("Pattern workers stopped unexpectedly."))?;
+        self.order.push_back(offset);
+        Ok(())
+    }
+
+    pub(super) fn next(&mut self) -> io::Result<Option<Filled>> {
+        let Some(&offset) = self.order.front() else {
+            return Ok(None);
+        };
+        while !self.ready.contains_key(&offset) {
+            let filled = self
+                .done
+                .recv()
+                .map_err(|_| io::Error::other("Pattern workers stopped unexpectedly."))?;
+            self.ready.insert(filled.offset, filled);
+        }
+        self.order.pop_front();
+        Ok(self.ready.remove(&offset))
+    }
+}
+
+// Reads are issued from a dedicated thread that stays ahead of the comparison
+// by however many buffers are in the pool. Buffers come back through
+// `recycle`; dropping the handle stops the reader once its in-flight requests
+// have drained.
+pub(super) struct ReadAhead {
+    ready: Receiver<io::Result<Completed>>,
+    free: Sender<AlignedBuffer>,
+}
+
+impl ReadAhead {
+    pub(super) fn spawn<'scope>(
+        scope: &'scope Scope<'scope, '_>,
+        mut queue: BlockQueue,
+        mut buffers: Vec<AlignedBuffer>,
+        blocks: BlockOrder,
+    ) -> Self {
+        let (ready_tx, ready) = mpsc::channel();
+        let (free, free_rx) = mpsc::channel::<AlignedBuffer>();
+        scope.spawn(move || {
+            let mut blocks = blocks.peekable();
+            loop {
+                while let Ok(buffer) = free_rx.try_recv() {
+                    buffers.push(buffer);
+                }
+                while let Some(&(offset, len)) = blocks.peek()
+                    && let Some(buffer) = buffers.pop()
+                {
+                    blocks.next();
+                    if let Err(e) = queue.push_read(offset, buffer, len) {
+                        let _ = ready_tx.send(Err(e));
+                        return;
+                    }
+                }
+                match queue.pop() {
+                    Ok(Some(done)) => {
+                        if ready_tx.send(Ok(done)).is_err() {
+                            return;
+                        }
+                    }
+                    Ok(None) if blocks.peek().is_none() => return,
+                    Ok(None) => match free_rx.recv() {
+                        Ok(buffer) => buffers.push(buffer),
+                        Err(_) => return,
+                    },
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(e));
+                        return;
+                    }
+                }
+            }
+        });
+        Self { ready, free }
+    }
+
+    // None once every block in the range has been handed out.
+    pub(super) fn next(&mut self) -> io::Result<Option<Completed>> {
+        match self.ready.recv() {
+            Ok(done) => done.map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+
+    pub(super) fn recycle(&self, buffer: AlignedBuffer) {
+        let _ = self.free.send(buffer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::FillPipeline;
+    use crate::core_logic::{self, Pattern, PatternLayout, SPLITMIX};
+    use crate::io_controller::direct_io::AlignedBuffer;
+
+    #[test]
+    fn test_filled_blocks_come_back_in_submission_order() {
+        const BLOCK: usize = 4096;
+        let pattern = Pattern::new(0x1234, PatternLayout::Plain, &SPLITMIX);
+        thread::scope(|scope| {
+            let mut fills = FillPipeline::spawn(scope, pattern, 4);
+            for block in 0..16u64 {
+                let buffer = AlignedBuffer::new(BLOCK, 4096).unwrap();
+                fills.submit(block * BLOCK as u64, BLOCK, buffer).unwrap();
+            }
+            for block in 0..16u64 {
+                let mut filled = fills.next().unwrap().unwrap();
+                assert_eq!(filled.offset, block * BLOCK as u64);
+                let mut expected = vec![0u8; BLOCK];
+                core_logic::fill_block(pattern, filled.offset, &mut expected);
+                assert_eq!(filled.buffer.as_mut_slice(), &expected[..]);
+            }
+            assert!(fills.next().unwrap().is_none());
+        });
+    }
+}
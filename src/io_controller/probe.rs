@@ -1,10 +1,9 @@
 use std::io::{self, ErrorKind, Read, Seek, SeekFrom, Write};
 use std::path::Path;
 
-use crate::core_logic::{self, DiagnosisReport, DriveHealthStatus};
+use crate::core_logic::{self, DiagnosisReport, MisplacedSector, Pattern, ScanStats};
 
-use super::DIRECT_IO_ALIGNMENT;
-use super::direct_io::{AlignedBuffer, align_down_u64, open_direct_read, open_direct_write, resolve_block_size};
+use super::direct_io::{AlignedBuffer, align_down_u64, evict_cache, resolve_block_size};
 
 impl super::DriveInspector {
     pub fn run_quick_probe_phase(
@@ -13,17 +12,21 @@ impl super::DriveInspector {
         steps: usize,
     ) -> io::Result<Option<DiagnosisReport>> {
         let path = Path::new(&self.file_path);
-        if let Some(parent) = path.parent() {
-            if !parent.exists() {
-                return Err(io::Error::new(
-                    ErrorKind::NotFound,
-                    "Parent directory does not exist.",
-                ));
-            }
+        if let Some(parent) = path.parent()
+            && !parent.exists()
+        {
+            return Err(io::Error::new(
+                ErrorKind::NotFound,
+                "Parent directory does not exist.",
+            ));
+        }
+        if self.multi_file {
+            println!("[INFO] Quick probe skipped: multi-file mode.");
+            return Ok(None);
         }
-        let block_size = resolve_block_size(self.block_size)?;
-        if limit_mb == 0 {
-            println!("[INFO] Quick probe skipped: no limit provided.");
+        let limit_bytes_raw = self.limit_bytes(limit_mb)?;
+        if limit_bytes_raw == u64::MAX {
+            println!("[INFO] Quick probe skipped: target size unknown.");
             return Ok(None);
         }
         if steps < 2 {
@@ -32,8 +35,12 @@ impl super::DriveInspector {
                 "Quick probe steps must be at least 2.",
             ));
         }
-        let limit_bytes_raw = limit_mb * 1024 * 1024;
-        let limit_bytes = align_down_u64(limit_bytes_raw, DIRECT_IO_ALIGNMENT as u64);
+        self.forget_previous_run()?;
+        let mut file = self.open_target_write(path)?;
+        let geometry = self.detect_geometry(&file);
+        let alignment = geometry.alignment as usize;
+        let block_size = resolve_block_size(self.block_size, alignment)?;
+        let limit_bytes = align_down_u64(limit_bytes_raw, alignment as u64);
         if limit_bytes < block_size as u64 {
             return Err(io::Error::new(
                 ErrorKind::InvalidInput,
@@ -54,54 +61,52 @@ impl super::DriveInspector {
             limit_bytes / 1024 / 1024
         );
 
-        let mut file = open_direct_write(path)?;
-        let mut buffer = AlignedBuffer::new(block_size, DIRECT_IO_ALIGNMENT)?;
+        let mut buffer = AlignedBuffer::new(block_size, alignment)?;
 
         for &offset in &offsets {
             file.seek(SeekFrom::Start(offset))?;
-            core_logic::fill_block(offset, buffer.as_mut_slice());
+            core_logic::fill_block(self.pattern, offset, buffer.as_mut_slice());
             file.write_all(buffer.as_mut_slice())?;
         }
         file.sync_all()?;
+        if self.is_buffered() {
+            evict_cache(&file)?;
+        }
 
-        let mut verify = open_direct_read(path)?;
-        let mut mismatch_blocks: u64 = 0;
-        let mut read_error_blocks: u64 = 0;
-        let mut valid_bytes: u64 = 0;
-        let mut sample_status: Option<DriveHealthStatus> = None;
+        let mut verify = self.open_target_read(path)?;
+        let mut stats = ScanStats {
+            geometry: Some(geometry),
+            buffered_io: self.is_buffered(),
+            ..ScanStats::default()
+        };
 
         for &offset in &offsets {
             verify.seek(SeekFrom::Start(offset))?;
             if let Err(e) = verify.read_exact(buffer.as_mut_slice()) {
-                read_error_blocks += 1;
-                println!("[ERROR] Quick probe read failure at offset {}: {}", offset, e);
+                stats.record_read_error(offset, block_size as u64);
+                println!(
+                    "[ERROR] Quick probe read failure at offset {}: {}",
+                    offset, e
+                );
                 continue;
             }
 
-            if core_logic::verify_block(offset, buffer.as_mut_slice()).is_ok() {
-                valid_bytes += block_size as u64;
+            if core_logic::verify_block(self.pattern, offset, buffer.as_mut_slice()).is_ok() {
+                stats.record_valid(offset, block_size as u64);
                 continue;
             }
 
-            mismatch_blocks += 1;
-            if matches_other_anchor(&offsets, offset, buffer.as_mut_slice()) {
-                sample_status = Some(DriveHealthStatus::FakeCapacity);
-                break;
-            }
-            if sample_status.is_none() {
-                sample_status = Some(DriveHealthStatus::PhysicalCorruption);
-            }
+            record_block_mismatch(
+                self.pattern,
+                offset,
+                buffer.as_mut_slice(),
+                &offsets,
+                &mut stats,
+            );
         }
 
         let tested_bytes = offsets.len() as u64 * block_size as u64;
-        let report = core_logic::generate_report(
-            limit_bytes,
-            tested_bytes,
-            valid_bytes,
-            mismatch_blocks,
-            read_error_blocks,
-            sample_status,
-        );
+        let report = core_logic::generate_report(limit_bytes, tested_bytes, &stats, self.pattern);
 
         if report.error_count == 0 {
             println!("[INFO] Quick probe complete: no anomalies.");
@@ -116,29 +121,57 @@ impl super::DriveInspector {
     }
 }
 
+// Records a block that read back wrong, with whatever can be said about why:
+// data that belongs at another written offset, or a recognisable failure mode.
+pub(super) fn record_block_mismatch(
+    pattern: Pattern,
+    offset: u64,
+    actual: &[u8],
+    written: &[u64],
+    stats: &mut ScanStats,
+) {
+    let extents = core_logic::mismatch_extents(pattern, offset, actual);
+    stats.record_mismatch(offset, actual.len() as u64, &extents);
+    let misplaced = core_logic::find_misplaced_sector(pattern.session_seed, offset, actual)
+        .or_else(|| {
+            core_logic::find_matching_offset(pattern, written, offset, actual).map(
+                |written_offset| MisplacedSector {
+                    read_offset: offset,
+                    written_offset,
+                },
+            )
+        });
+    if let Some(sector) = misplaced {
+        stats.record_misplaced(sector);
+    }
+    let mut expected = vec![0u8; actual.len()];
+    core_logic::fill_block(pattern, offset, &mut expected);
+    if let Some(kind) = core_logic::classify_failure(
+        pattern,
+        offset,
+        &expected,
+        actual,
+        misplaced.is_some(),
+        &mut stats.fingerprints,
+    ) {
+        stats.record_failure(kind);
+    }
+}
+
 fn compute_probe_offsets(total_bytes: u64, block_size: usize, steps: usize) -> Vec<u64> {
     let mut offsets = Vec::with_capacity(steps + 1);
     if total_bytes == 0 || block_size == 0 {
         return offsets;
     }
-    let last_offset = total_bytes.saturating_sub(block_size as u64);
+    // Anchors sit on the block grid so they never overlap each other and line
+    // up with the blocks a full write would produce.
+    let block = block_size as u64;
+    let last_offset = align_down_u64(total_bytes, block).saturating_sub(block);
     for step in 0..=steps {
         let numerator = total_bytes.saturating_mul(step as u64);
         let raw = numerator / steps as u64;
-        let aligned = align_down_u64(raw, DIRECT_IO_ALIGNMENT as u64);
+        let aligned = align_down_u64(raw, block);
         offsets.push(aligned.min(last_offset));
     }
     offsets
 }
-
-fn matches_other_anchor(offsets: &[u64], current: u64, buffer: &[u8]) -> bool {
-    for &offset in offsets {
-        if offset == current {
-            continue;
-        }
-        if core_logic::verify_block(offset, buffer).is_ok() {
-            return true;
-        }
-    }
-    false
-}
@@ -21,9 +21,21 @@ pub struct ProgressUpdate {
     pub total_bytes: u64,
 }
 
+// A request that has run past the stall threshold. Sent while the device is
+// still quiet, with no offset, and again once the slow request returns.
+#[derive(Debug, Clone, Serialize)]
+pub struct StallEvent {
+    pub phase: ProgressPhase,
+    pub offset: Option<u64>,
+    pub duration_ms: u64,
+    pub finished: bool,
+}
+
 pub trait EventSink: Send + Sync {
     fn progress(&self, update: ProgressUpdate);
     fn error(&self, message: String);
+
+    fn stall(&self, _event: StallEvent) {}
 }
 
 pub(super) fn should_cancel(cancel_flag: &Option<Arc<AtomicBool>>) -> bool {
@@ -55,6 +67,12 @@ pub(super) fn emit_progress(sink: Option<&dyn EventSink>, update: ProgressUpdate
     }
 }
 
+pub(super) fn emit_stall(sink: Option<&dyn EventSink>, event: StallEvent) {
+    if let Some(sink) = sink {
+        sink.stall(event);
+    }
+}
+
 pub(super) fn emit_error(sink: Option<&dyn EventSink>, message: String) {
     if let Some(sink) = sink {
         sink.error(message);
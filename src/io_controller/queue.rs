@@ -0,0 +1,317 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, ErrorKind};
+use std::time::{Duration, Instant};
+
+use super::direct_io::{AlignedBuffer, read_at, write_at};
+#[cfg(target_os = "linux")]
+use super::uring::Uring;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IoEngine {
+    #[default]
+    Blocking,
+    IoUring,
+}
+
+impl IoEngine {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "blocking" => Some(IoEngine::Blocking),
+            "io_uring" | "uring" => Some(IoEngine::IoUring),
+            _ => None,
+        }
+    }
+}
+
+// `latency` runs from submission until the completion was collected, so with
+// io_uring it can include time the completion spent waiting behind an older
+// request.
+pub(super) struct Completed {
+    pub(super) offset: u64,
+    pub(super) len: usize,
+    pub(super) buffer: AlignedBuffer,
+    pub(super) result: io::Result<usize>,
+    pub(super) latency: Duration,
+    submitted: Instant,
+}
+
+impl Completed {
+    pub(super) fn is_complete(&self) -> bool {
+        matches!(self.result, Ok(done) if done == self.len)
+    }
+}
+
+enum Backend {
+    Blocking,
+    #[cfg(target_os = "linux")]
+    Uring(Uring),
+}
+
+// Requests complete in whatever order the device finishes them but are handed
+// back strictly in submission order, so the phases can keep treating the file
+// as one sequential stream.
+pub(super) struct BlockQueue {
+    file: File,
+    backend: Backend,
+    depth: usize,
+    pending: VecDeque<Completed>,
+    resolved: VecDeque<bool>,
+    front_tag: u64,
+}
+
+impl BlockQueue {
+    pub(super) fn new(engine: IoEngine, file: File, depth: usize) -> io::Result<Self> {
+        let backend = match engine {
+            IoEngine::Blocking => Backend::Blocking,
+            #[cfg(target_os = "linux")]
+            IoEngine::IoUring => match Uring::new(depth.max(1) as u32) {
+                Ok(ring) => Backend::Uring(ring),
+                Err(e) => {
+                    println!("[INFO] io_uring unavailable ({}); using blocking I/O.", e);
+                    Backend::Blocking
+                }
+            },
+            #[cfg(not(target_os = "linux"))]
+            IoEngine::IoUring => {
+                println!("[INFO] io_uring is only available on Linux; using blocking I/O.");
+                Backend::Blocking
+            }
+        };
+        let depth = match &backend {
+            Backend::Blocking => 1,
+            #[cfg(target_os = "linux")]
+            Backend::Uring(ring) => depth.clamp(1, ring.entries() as usize),
+        };
+        Ok(Self {
+            file,
+            backend,
+            depth,
+            pending: VecDeque::with_capacity(depth),
+            resolved: VecDeque::with_capacity(depth),
+            front_tag: 0,
+        })
+    }
+
+    pub(super) fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub(super) fn in_flight(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub(super) fn push_read(
+        &mut self,
+        offset: u64,
+        mut buffer: AlignedBuffer,
+        len: usize,
+    ) -> io::Result<()> {
+        let tag = self.front_tag + self.pending.len() as u64;
+        let submitted = Instant::now();
+        let result = match &mut self.backend {
+            Backend::Blocking => Some(read_full(
+                &self.file,
+                offset,
+                &mut buffer.as_mut_slice()[..len],
+            )),
+            #[cfg(target_os = "linux")]
+            Backend::Uring(ring) => {
+                unsafe { ring.push_read(&self.file, offset, buffer.as_mut_ptr(), len, tag) };
+                ring.submit()?;
+                None
+            }
+        };
+        self.enqueue(offset, len, buffer, result, submitted);
+        Ok(())
+    }
+
+    pub(super) fn push_write(
+        &mut self,
+        offset: u64,
+        mut buffer: AlignedBuffer,
+        len: usize,
+    ) -> io::Result<()> {
+        let tag = self.front_tag + self.pending.len() as u64;
+        let submitted = Instant::now();
+        let result = match &mut self.backend {
+            Backend::Blocking => Some(write_full(
+                &self.file,
+                offset,
+                &buffer.as_mut_slice()[..len],
+            )),
+            #[cfg(target_os = "linux")]
+            Backend::Uring(ring) => {
+                unsafe { ring.push_write(&self.file, offset, buffer.as_mut_ptr(), len, tag) };
+                ring.submit()?;
+                None
+            }
+        };
+        self.enqueue(offset, len, buffer, result, submitted);
+        Ok(())
+    }
+
+    fn enqueue(
+        &mut self,
+        offset: u64,
+        len: usize,
+        buffer: AlignedBuffer,
+        result: Option<io::Result<usize>>,
+        submitted: Instant,
+    ) {
+        self.resolved.push_back(result.is_some());
+        self.pending.push_back(Completed {
+            offset,
+            len,
+            buffer,
+            result: result.unwrap_or(Ok(0)),
+            latency: submitted.elapsed(),
+            submitted,
+        });
+    }
+
+    // Returns the oldest request once it has finished, or None when nothing is
+    // in flight.
+    pub(super) fn pop(&mut self) -> io::Result<Option<Completed>> {
+        if self.pending.is_empty() {
+            return Ok(None);
+        }
+        while !self.resolved[0] {
+            self.wait_one()?;
+        }
+        self.resolved.pop_front();
+        self.front_tag += 1;
+        Ok(self.pending.pop_front())
+    }
+
+    fn wait_one(&mut self) -> io::Result<()> {
+        match &mut self.backend {
+            Backend::Blocking => Ok(()),
+            #[cfg(target_os = "linux")]
+            Backend::Uring(ring) => {
+                let completion = ring.wait()?;
+                let index = completion.user_data.wrapping_sub(self.front_tag) as usize;
+                if index >= self.pending.len() {
+                    return Err(io::Error::other("io_uring returned an unknown request."));
+                }
+                let done = &mut self.pending[index];
+                done.result = completion.result;
+                done.latency = done.submitted.elapsed();
+                self.resolved[index] = true;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Drop for BlockQueue {
+    // The kernel may still be filling or draining buffers we own; they must
+    // not be freed until every request has completed.
+    fn drop(&mut self) {
+        while self.resolved.iter().any(|done| !done) {
+            if self.wait_one().is_err() {
+                std::mem::forget(std::mem::take(&mut self.pending));
+                return;
+            }
+        }
+    }
+}
+
+fn read_full(file: &File, offset: u64, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut done = 0;
+    while done < buffer.len() {
+        match read_at(file, &mut buffer[done..], offset + done as u64) {
+            Ok(0) => break,
+            Ok(count) => done += count,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(done)
+}
+
+// A short count means the device ran out of space part-way through the block.
+fn write_full(file: &File, offset: u64, buffer: &[u8]) -> io::Result<usize> {
+    let mut done = 0;
+    while done < buffer.len() {
+        match write_at(file, &buffer[done..], offset + done as u64) {
+            Ok(0) => break,
+            Ok(count) => done += count,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) if done > 0 && is_storage_full(&e) => break,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(done)
+}
+
+// EFBIG counts too: the file cannot grow, even if the volume has room left.
+pub(super) fn is_storage_full(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        ErrorKind::WriteZero | ErrorKind::StorageFull | ErrorKind::FileTooLarge
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::OpenOptions;
+
+    use super::{BlockQueue, IoEngine};
+    use crate::io_controller::direct_io::AlignedBuffer;
+
+    #[test]
+    fn test_queued_blocks_round_trip_in_order() {
+        const BLOCK: usize = 8192;
+        let path = std::env::temp_dir().join(format!("truthbyte-queue-{}", std::process::id()));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .unwrap();
+
+        for engine in [IoEngine::Blocking, IoEngine::IoUring] {
+            let mut queue = BlockQueue::new(engine, file.try_clone().unwrap(), 4).unwrap();
+            for block in 0..8u8 {
+                let mut buffer = AlignedBuffer::new(BLOCK, 4096).unwrap();
+                buffer.as_mut_slice().fill(block);
+                if queue.in_flight() >= queue.depth() {
+                    assert!(queue.pop().unwrap().unwrap().is_complete());
+                }
+                queue
+                    .push_write(block as u64 * BLOCK as u64, buffer, BLOCK)
+                    .unwrap();
+            }
+            while let Some(done) = queue.pop().unwrap() {
+                assert!(done.is_complete());
+            }
+
+            let mut seen = Vec::new();
+            for block in 0..8u64 {
+                if queue.in_flight() >= queue.depth() {
+                    seen.push(queue.pop().unwrap().unwrap());
+                }
+                let buffer = AlignedBuffer::new(BLOCK, 4096).unwrap();
+                queue
+                    .push_read(block * BLOCK as u64, buffer, BLOCK)
+                    .unwrap();
+            }
+            while let Some(done) = queue.pop().unwrap() {
+                seen.push(done);
+            }
+            for (index, mut done) in seen.into_iter().enumerate() {
+                assert!(done.is_complete());
+                assert_eq!(done.offset, index as u64 * BLOCK as u64);
+                assert!(
+                    done.buffer
+                        .as_mut_slice()
+                        .iter()
+                        .all(|&byte| byte == index as u8)
+                );
+            }
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,89 @@
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+// Multi-file mode spreads one logical test stream over numbered files in the
+// target directory. Offsets stay global, so the pattern carries on across file
+// boundaries and the verify phase can treat the series as a single range.
+pub(super) struct Segment {
+    pub(super) path: PathBuf,
+    pub(super) start: u64,
+    pub(super) len: u64,
+}
+
+pub fn segment_path(dir: &Path, index: usize) -> PathBuf {
+    dir.join(format!("tb{:05}.dat", index))
+}
+
+// Clears the series left by an earlier run so its space is available again and
+// a shorter run does not leave stale files at the end.
+pub(super) fn remove_segments(dir: &Path) -> io::Result<()> {
+    let mut index = 0;
+    loop {
+        match fs::remove_file(segment_path(dir, index)) {
+            Ok(()) => index += 1,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+// Walks the series in order until `total_bytes` are covered or a file is
+// missing; the last segment is trimmed to the bytes that were written.
+pub(super) fn discover_segments(dir: &Path, total_bytes: u64) -> io::Result<Vec<Segment>> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    while start < total_bytes {
+        let path = segment_path(dir, segments.len());
+        let len = match fs::metadata(&path) {
+            Ok(metadata) => metadata.len().min(total_bytes - start),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => break,
+            Err(e) => return Err(e),
+        };
+        if len == 0 {
+            break;
+        }
+        segments.push(Segment { path, start, len });
+        start += len;
+    }
+    Ok(segments)
+}
+
+// Cuts a global range at segment boundaries, returning the index of each
+// segment it touches with the overlapping part.
+pub(super) fn split_at_segments(
+    range: Range<u64>,
+    segments: &[Segment],
+) -> Vec<(usize, Range<u64>)> {
+    segments
+        .iter()
+        .enumerate()
+        .filter_map(|(index, segment)| {
+            let start = range.start.max(segment.start);
+            let end = range.end.min(segment.start + segment.len);
+            (start < end).then_some((index, start..end))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{Segment, split_at_segments};
+
+    #[test]
+    fn test_ranges_split_at_segment_boundaries() {
+        let segments = [(0, 100), (100, 60), (160, 200)].map(|(start, len)| Segment {
+            path: PathBuf::new(),
+            start,
+            len,
+        });
+        assert_eq!(
+            split_at_segments(50..200, &segments),
+            vec![(0, 50..100), (1, 100..160), (2, 160..200)]
+        );
+        assert_eq!(split_at_segments(160..360, &segments), vec![(2, 160..360)]);
+    }
+}
@@ -0,0 +1,47 @@
+use std::time::Instant;
+
+use crate::core_logic::ThroughputCurve;
+
+// Closes a point of the curve each time another region's worth of bytes has
+// completed. A region that is still open when the phase ends is dropped, since
+// its speed would cover too little data to mean much.
+pub(super) struct ThroughputRecorder {
+    curve: ThroughputCurve,
+    start: Instant,
+    done: u64,
+    region_start: Instant,
+    region_start_bytes: u64,
+}
+
+impl ThroughputRecorder {
+    pub(super) fn new(region_bytes: u64, already_done: u64) -> Self {
+        let now = Instant::now();
+        Self {
+            curve: ThroughputCurve::new(region_bytes.max(1)),
+            start: now,
+            done: already_done,
+            region_start: now,
+            region_start_bytes: already_done,
+        }
+    }
+
+    pub(super) fn record(&mut self, bytes: u64) {
+        self.done += bytes;
+        let region_bytes = self.done - self.region_start_bytes;
+        if region_bytes >= self.curve.region_bytes {
+            let now = Instant::now();
+            self.curve.push(
+                self.done,
+                now - self.start,
+                region_bytes,
+                now - self.region_start,
+            );
+            self.region_start = now;
+            self.region_start_bytes = self.done;
+        }
+    }
+
+    pub(super) fn curve(&self) -> ThroughputCurve {
+        self.curve.clone()
+    }
+}
@@ -0,0 +1,335 @@
+use std::fs::File;
+use std::io;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::ptr;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+// Kernel ABI from <linux/io_uring.h>. Only plain IORING_OP_READ/WRITE on a
+// regular fd are used, which needs Linux 5.6 or newer.
+const IORING_OFF_SQ_RING: libc::off_t = 0;
+const IORING_OFF_CQ_RING: libc::off_t = 0x0800_0000;
+const IORING_OFF_SQES: libc::off_t = 0x1000_0000;
+const IORING_ENTER_GETEVENTS: u32 = 1;
+const IORING_OP_READ: u8 = 22;
+const IORING_OP_WRITE: u8 = 23;
+
+#[repr(C)]
+#[derive(Default)]
+struct SqringOffsets {
+    head: u32,
+    tail: u32,
+    ring_mask: u32,
+    ring_entries: u32,
+    flags: u32,
+    dropped: u32,
+    array: u32,
+    resv1: u32,
+    user_addr: u64,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct CqringOffsets {
+    head: u32,
+    tail: u32,
+    ring_mask: u32,
+    ring_entries: u32,
+    overflow: u32,
+    cqes: u32,
+    flags: u32,
+    resv1: u32,
+    user_addr: u64,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct Params {
+    sq_entries: u32,
+    cq_entries: u32,
+    flags: u32,
+    sq_thread_cpu: u32,
+    sq_thread_idle: u32,
+    features: u32,
+    wq_fd: u32,
+    resv: [u32; 3],
+    sq_off: SqringOffsets,
+    cq_off: CqringOffsets,
+}
+
+#[repr(C)]
+struct Sqe {
+    opcode: u8,
+    flags: u8,
+    ioprio: u16,
+    fd: i32,
+    off: u64,
+    addr: u64,
+    len: u32,
+    rw_flags: u32,
+    user_data: u64,
+    buf_index: u16,
+    personality: u16,
+    splice_fd_in: i32,
+    addr3: u64,
+    pad: u64,
+}
+
+#[repr(C)]
+struct Cqe {
+    user_data: u64,
+    res: i32,
+    flags: u32,
+}
+
+struct Mapping {
+    ptr: *mut u8,
+    len: usize,
+}
+
+impl Mapping {
+    fn new(fd: &OwnedFd, len: usize, offset: libc::off_t) -> io::Result<Self> {
+        let ptr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED | libc::MAP_POPULATE,
+                fd.as_raw_fd(),
+                offset,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self {
+            ptr: ptr as *mut u8,
+            len,
+        })
+    }
+
+    fn at<T>(&self, offset: u32) -> *mut T {
+        unsafe { self.ptr.add(offset as usize) as *mut T }
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.ptr as *mut libc::c_void, self.len);
+        }
+    }
+}
+
+pub(super) struct Completion {
+    pub(super) user_data: u64,
+    pub(super) result: io::Result<usize>,
+}
+
+// A single-threaded ring: the caller never has more requests in flight than
+// there are submission entries, so submission cannot overflow and every
+// completion fits in the completion ring.
+pub(super) struct Uring {
+    fd: OwnedFd,
+    sq_ring: Mapping,
+    cq_ring: Mapping,
+    sqes: Mapping,
+    params: Params,
+    to_submit: u32,
+}
+
+// The mappings are only ever touched through `&mut self`, so moving the ring to
+// the thread that drives it is sound.
+unsafe impl Send for Uring {}
+
+impl Uring {
+    pub(super) fn new(entries: u32) -> io::Result<Self> {
+        let mut params = Params::default();
+        let fd = unsafe {
+            libc::syscall(
+                libc::SYS_io_uring_setup,
+                entries,
+                &mut params as *mut Params,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(fd as i32) };
+
+        let sq_len = params.sq_off.array as usize + params.sq_entries as usize * 4;
+        let cq_len = params.cq_off.cqes as usize + params.cq_entries as usize * size_of::<Cqe>();
+        let sqes_len = params.sq_entries as usize * size_of::<Sqe>();
+        let sq_ring = Mapping::new(&fd, sq_len, IORING_OFF_SQ_RING)?;
+        let cq_ring = Mapping::new(&fd, cq_len, IORING_OFF_CQ_RING)?;
+        let sqes = Mapping::new(&fd, sqes_len, IORING_OFF_SQES)?;
+
+        Ok(Self {
+            fd,
+            sq_ring,
+            cq_ring,
+            sqes,
+            params,
+            to_submit: 0,
+        })
+    }
+
+    pub(super) fn entries(&self) -> u32 {
+        self.params.sq_entries
+    }
+
+    fn sq_atomic(&self, offset: u32) -> &AtomicU32 {
+        unsafe { &*self.sq_ring.at::<AtomicU32>(offset) }
+    }
+
+    fn cq_atomic(&self, offset: u32) -> &AtomicU32 {
+        unsafe { &*self.cq_ring.at::<AtomicU32>(offset) }
+    }
+
+    // The buffer must stay valid and untouched until its completion is reaped.
+    pub(super) unsafe fn push_read(
+        &mut self,
+        file: &File,
+        offset: u64,
+        buffer: *mut u8,
+        len: usize,
+        user_data: u64,
+    ) {
+        self.push(IORING_OP_READ, file, offset, buffer, len, user_data);
+    }
+
+    pub(super) unsafe fn push_write(
+        &mut self,
+        file: &File,
+        offset: u64,
+        buffer: *const u8,
+        len: usize,
+        user_data: u64,
+    ) {
+        self.push(
+            IORING_OP_WRITE,
+            file,
+            offset,
+            buffer as *mut u8,
+            len,
+            user_data,
+        );
+    }
+
+    fn push(
+        &mut self,
+        opcode: u8,
+        file: &File,
+        offset: u64,
+        buffer: *mut u8,
+        len: usize,
+        user_data: u64,
+    ) {
+        let mask = unsafe { *self.sq_ring.at::<u32>(self.params.sq_off.ring_mask) };
+        let tail = self
+            .sq_atomic(self.params.sq_off.tail)
+            .load(Ordering::Relaxed);
+        let index = tail & mask;
+        let sqe = Sqe {
+            opcode,
+            flags: 0,
+            ioprio: 0,
+            fd: file.as_raw_fd(),
+            off: offset,
+            addr: buffer as u64,
+            len: len as u32,
+            rw_flags: 0,
+            user_data,
+            buf_index: 0,
+            personality: 0,
+            splice_fd_in: 0,
+            addr3: 0,
+            pad: 0,
+        };
+        unsafe {
+            self.sqes.at::<Sqe>(0).add(index as usize).write(sqe);
+            self.sq_ring
+                .at::<u32>(self.params.sq_off.array)
+                .add(index as usize)
+                .write(index);
+        }
+        self.sq_atomic(self.params.sq_off.tail)
+            .store(tail.wrapping_add(1), Ordering::Release);
+        self.to_submit += 1;
+    }
+
+    pub(super) fn submit(&mut self) -> io::Result<()> {
+        while self.to_submit > 0 {
+            self.enter(0)?;
+        }
+        Ok(())
+    }
+
+    // Submits everything queued so far and blocks until at least one
+    // completion is available.
+    pub(super) fn wait(&mut self) -> io::Result<Completion> {
+        loop {
+            if let Some(completion) = self.reap() {
+                return Ok(completion);
+            }
+            self.enter(1)?;
+        }
+    }
+
+    fn enter(&mut self, min_complete: u32) -> io::Result<()> {
+        let flags = if min_complete > 0 {
+            IORING_ENTER_GETEVENTS
+        } else {
+            0
+        };
+        let submitted = unsafe {
+            libc::syscall(
+                libc::SYS_io_uring_enter,
+                self.fd.as_raw_fd(),
+                self.to_submit,
+                min_complete,
+                flags,
+                ptr::null::<libc::sigset_t>(),
+                0usize,
+            )
+        };
+        if submitted < 0 {
+            let error = io::Error::last_os_error();
+            if error.kind() == io::ErrorKind::Interrupted {
+                return Ok(());
+            }
+            return Err(error);
+        }
+        self.to_submit -= (submitted as u32).min(self.to_submit);
+        Ok(())
+    }
+
+    fn reap(&mut self) -> Option<Completion> {
+        let head = self
+            .cq_atomic(self.params.cq_off.head)
+            .load(Ordering::Relaxed);
+        let tail = self
+            .cq_atomic(self.params.cq_off.tail)
+            .load(Ordering::Acquire);
+        if head == tail {
+            return None;
+        }
+        let mask = unsafe { *self.cq_ring.at::<u32>(self.params.cq_off.ring_mask) };
+        let cqe = unsafe {
+            self.cq_ring
+                .at::<Cqe>(self.params.cq_off.cqes)
+                .add((head & mask) as usize)
+                .read()
+        };
+        self.cq_atomic(self.params.cq_off.head)
+            .store(head.wrapping_add(1), Ordering::Release);
+        let result = if cqe.res < 0 {
+            Err(io::Error::from_raw_os_error(-cqe.res))
+        } else {
+            Ok(cqe.res as usize)
+        };
+        Some(Completion {
+            user_data: cqe.user_data,
+            result,
+        })
+    }
+}
@@ -1,206 +1,370 @@
+use std::fs::File;
 use std::io::{self, ErrorKind, Read, Seek, SeekFrom};
-use std::sync::Arc;
-use std::sync::atomic::AtomicBool;
-use std::time::Instant;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 
-use crate::core_logic::{self, DiagnosisReport, DriveHealthStatus};
+use crate::core_logic::{
+    self, CacheEstimateSkip, DiagnosisReport, DriveHealthStatus, FailureKind, MisplacedSector,
+    ScanStats,
+};
 
-use super::DIRECT_IO_ALIGNMENT;
-use super::direct_io::{AlignedBuffer, open_direct_read, resolve_block_size};
+use super::checkpoint::{CHECKPOINT_INTERVAL, Checkpoint, CheckpointPhase, RegionProgress};
+use super::direct_io::{AlignedBuffer, device_error_status, evict_cache, resolve_block_size};
+use super::order::BlockOrder;
+use super::pipeline::ReadAhead;
 use super::progress::{emit_error, emit_progress, percent_of, should_cancel, speed_mbps};
+use super::queue::BlockQueue;
+use super::segments::{Segment, discover_segments, split_at_segments};
+use super::throughput::ThroughputRecorder;
+use super::watchdog::IoWatchdog;
+use super::write::WriteSummary;
+
+const MAX_READ_RETRIES: usize = 3;
+const MAX_CONSECUTIVE_BAD_BLOCKS: u64 = 1000;
+const MAX_ALIAS_SEARCHES: u32 = 64;
+const MAX_REREADS: u32 = 64;
+const READ_AHEAD_BLOCKS: usize = 2;
+const PROGRESS_PUBLISH_INTERVAL: Duration = Duration::from_secs(1);
+// Keeps the verify order apart from the write order even when both are random,
+// so a controller cannot replay reads in the order it took the writes.
+const VERIFY_ORDER_SALT: u64 = 0x5645_5249_4659_4f52;
+
+// State every region thread reports into. Progress is only ever emitted from
+// the coordinating thread, which reads the summed counters, so updates stay
+// monotonic however the regions interleave.
+struct VerifyShared<'a> {
+    total_bytes: u64,
+    block_size: usize,
+    alignment: usize,
+    verified: AtomicU64,
+    errors: AtomicU64,
+    stop: AtomicBool,
+    cancel_flag: Option<Arc<AtomicBool>>,
+    sink: Option<&'a dyn super::EventSink>,
+    watchdog: IoWatchdog<'a>,
+    throughput: Mutex<ThroughputRecorder>,
+    // Where each region has got to, for checkpoints; absent when this phase
+    // is not saving any.
+    progress: Option<Mutex<Vec<RegionProgress>>>,
+}
+
+// Part of a region that lies within one file. Offsets are global; `base` is
+// the global offset at which the file starts.
+struct RegionPiece {
+    file: File,
+    base: u64,
+    range: Range<u64>,
+}
+
+impl VerifyShared<'_> {
+    fn should_stop(&self) -> bool {
+        self.stop.load(Ordering::Relaxed) || should_cancel(&self.cancel_flag)
+    }
+
+    fn count_verified(&self, bytes: u64) {
+        self.verified.fetch_add(bytes, Ordering::Relaxed);
+        self.throughput.lock().unwrap().record(bytes);
+    }
+}
+
+// Regions are whole blocks, so every thread reads exactly the blocks a single
+// sequential pass would have read.
+fn split_regions(total_bytes: u64, block_size: u64, threads: usize) -> Vec<Range<u64>> {
+    let blocks = total_bytes.div_ceil(block_size);
+    let per_region = blocks.div_ceil(threads.max(1) as u64).max(1) * block_size;
+    (0..threads as u64)
+        .map(|index| {
+            let start = (index * per_region).min(total_bytes);
+            start..(start + per_region).min(total_bytes)
+        })
+        .filter(|region| !region.is_empty())
+        .collect()
+}
 
 impl super::DriveInspector {
-    pub fn run_verify_phase(&self, total_bytes: u64) -> io::Result<DiagnosisReport> {
-        self.run_verify_phase_with_events(total_bytes, None, None)
+    pub fn run_verify_phase(&self, written: &WriteSummary) -> io::Result<DiagnosisReport> {
+        self.run_verify_phase_with_events(written, None, None)
     }
 
     pub fn run_verify_phase_with_events(
         &self,
-        total_bytes: u64,
+        written: &WriteSummary,
         cancel_flag: Option<Arc<AtomicBool>>,
         sink: Option<&dyn super::EventSink>,
     ) -> io::Result<DiagnosisReport> {
-        const MAX_READ_RETRIES: usize = 3;
-        const MAX_CONSECUTIVE_BAD_BLOCKS: u64 = 1000;
-        let block_size = resolve_block_size(self.block_size)?;
-        if total_bytes % DIRECT_IO_ALIGNMENT as u64 != 0 {
+        self.verify_phase(written, None, cancel_flag, sink)
+    }
+
+    pub fn resume_verify_phase(&self, checkpoint: &Checkpoint) -> io::Result<DiagnosisReport> {
+        self.resume_verify_phase_with_events(checkpoint, None, None)
+    }
+
+    // Verifies what a checkpoint had not yet covered and reports on the whole
+    // run. The inspector must have been built `with_checkpoint`.
+    pub fn resume_verify_phase_with_events(
+        &self,
+        checkpoint: &Checkpoint,
+        cancel_flag: Option<Arc<AtomicBool>>,
+        sink: Option<&dyn super::EventSink>,
+    ) -> io::Result<DiagnosisReport> {
+        let written = WriteSummary {
+            bytes_written: checkpoint.bytes_written(),
+            speed_mbps: (checkpoint.write_speed_mbps > 0.0).then_some(checkpoint.write_speed_mbps),
+            cache_skip: Some(CacheEstimateSkip::ResumedWrite),
+            ..WriteSummary::default()
+        };
+        self.verify_phase(&written, Some(checkpoint), cancel_flag, sink)
+    }
+
+    fn verify_phase(
+        &self,
+        written: &WriteSummary,
+        resume: Option<&Checkpoint>,
+        cancel_flag: Option<Arc<AtomicBool>>,
+        sink: Option<&dyn super::EventSink>,
+    ) -> io::Result<DiagnosisReport> {
+        let total_bytes = written.bytes_written;
+        let path = Path::new(&self.file_path);
+        let segments = if self.multi_file {
+            let segments = discover_segments(path, total_bytes)?;
+            let found: u64 = segments.iter().map(|segment| segment.len).sum();
+            if found < total_bytes || segments.is_empty() {
+                return Err(io::Error::new(
+                    ErrorKind::NotFound,
+                    format!("Test file series ends after {found} of {total_bytes} bytes."),
+                ));
+            }
+            segments
+        } else {
+            vec![Segment {
+                path: path.to_path_buf(),
+                start: 0,
+                len: total_bytes,
+            }]
+        };
+        let open = |path: &Path| {
+            self.open_target_read(path).map_err(|e| {
+                emit_error(sink, format!("Unable to open target for reading: {}", e));
+                e
+            })
+        };
+
+        let first = open(&segments[0].path)?;
+        let geometry = self.detect_geometry(&first);
+        if self.is_buffered() {
+            for segment in &segments {
+                if let Err(e) = open(&segment.path).and_then(|file| evict_cache(&file)) {
+                    println!("[INFO] Unable to evict cached data before verify: {}", e);
+                }
+            }
+        }
+        let alignment = geometry.alignment as usize;
+        let block_size = resolve_block_size(self.block_size, alignment)?;
+        if let Some(written) = self.written_block_size
+            && written != block_size
+        {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Data was written in {} byte blocks, which cannot be read with the {} byte alignment this target needs.",
+                    written, alignment
+                ),
+            ));
+        }
+        if !total_bytes.is_multiple_of(alignment as u64) {
             return Err(io::Error::new(
                 ErrorKind::InvalidInput,
                 "Total bytes must be aligned for direct I/O.",
             ));
         }
-        let mut file = open_direct_read(std::path::Path::new(&self.file_path)).map_err(|e| {
-            emit_error(sink, format!("Unable to open target for reading: {}", e));
-            e
-        })?;
-        let mut buffer = AlignedBuffer::new(block_size, DIRECT_IO_ALIGNMENT)?;
-        let mut current_offset: u64 = 0;
-        let mut mismatch_blocks: u64 = 0;
-        let mut read_error_blocks: u64 = 0;
-        let mut valid_bytes: u64 = 0;
-        let mut consecutive_bad_blocks: u64 = 0;
-        let mut sample_status: Option<DriveHealthStatus> = None;
+        // A resumed verify keeps the regions it was checkpointed with, so each
+        // one carries on from its own position with its own counters.
+        let regions = match resume {
+            Some(checkpoint) if !checkpoint.regions.is_empty() => checkpoint.regions.clone(),
+            _ => split_regions(total_bytes, block_size as u64, self.verify_threads)
+                .into_iter()
+                .map(|region| RegionProgress {
+                    start: region.start,
+                    position: region.start,
+                    end: region.end,
+                    stats: ScanStats::default(),
+                })
+                .collect(),
+        };
+        let mut first = Some(first);
+        let mut region_pieces = Vec::with_capacity(regions.len());
+        for region in &regions {
+            let mut pieces = Vec::new();
+            for (index, range) in split_at_segments(region.start..region.end, &segments) {
+                let file = match first.take() {
+                    Some(file) if index == 0 => file,
+                    _ => open(&segments[index].path)?,
+                };
+                pieces.push(RegionPiece {
+                    file,
+                    base: segments[index].start,
+                    range,
+                });
+            }
+            region_pieces.push(pieces);
+        }
+        // A pending write checkpoint is left alone, so a cancelled write can
+        // still be resumed after the verify that follows it.
+        let checkpointing = (written.resumable || resume.is_some())
+            && !matches!(
+                self.read_checkpoint(),
+                Ok(Some(checkpoint)) if checkpoint.phase() == CheckpointPhase::Write
+            );
+        let already_verified: u64 = regions
+            .iter()
+            .map(|region| region.position - region.start)
+            .sum();
+        if already_verified > 0 {
+            println!("[INFO] Resuming verify after {} bytes.", already_verified);
+        }
+        let shared = VerifyShared {
+            total_bytes,
+            block_size,
+            alignment,
+            verified: AtomicU64::new(already_verified),
+            errors: AtomicU64::new(0),
+            stop: AtomicBool::new(false),
+            cancel_flag,
+            sink,
+            watchdog: IoWatchdog::new(super::ProgressPhase::Verify, self.stall_threshold, sink),
+            throughput: Mutex::new(ThroughputRecorder::new(
+                self.curve_region_bytes,
+                already_verified,
+            )),
+            progress: checkpointing.then(|| Mutex::new(regions.clone())),
+        };
+        let save_progress = || {
+            if let Some(progress) = &shared.progress {
+                let mut checkpoint = self.new_checkpoint(
+                    CheckpointPhase::Verify,
+                    total_bytes,
+                    total_bytes,
+                    written.speed_mbps.unwrap_or(0.0),
+                );
+                checkpoint.regions = progress.lock().unwrap().clone();
+                self.save_checkpoint(&checkpoint);
+            }
+        };
+        let mut last_checkpoint_time = Instant::now();
         let mut last_log_time = Instant::now();
         let mut last_emit_time = Instant::now();
         let start_time = Instant::now();
 
-        println!("[INFO] Verify phase start. Total Bytes={}", total_bytes);
+        println!(
+            "[INFO] Verify phase start. Total Bytes={}, Regions={}",
+            total_bytes,
+            regions.len()
+        );
 
-        while current_offset < total_bytes && !should_cancel(&cancel_flag) {
-            let remaining = total_bytes - current_offset;
-            let read_len = std::cmp::min(remaining, block_size as u64) as usize;
-            let target_buf = &mut buffer.as_mut_slice()[0..read_len];
+        let results = thread::scope(|scope| {
+            let (done_tx, done_rx) = mpsc::channel();
+            for (index, pieces) in region_pieces.into_iter().enumerate() {
+                let done_tx = done_tx.clone();
+                let shared = &shared;
+                let region = regions[index].clone();
+                scope.spawn(move || {
+                    let result = self.verify_region(index, region, pieces, shared);
+                    let _ = done_tx.send((index, result));
+                });
+            }
+            drop(done_tx);
 
-            let mut read_ok = false;
-            let mut last_error: Option<io::Error> = None;
-            for _ in 0..=MAX_READ_RETRIES {
-                if let Err(seek_err) = file.seek(SeekFrom::Start(current_offset)) {
-                    emit_error(
-                        sink,
-                        format!(
-                            "Unable to reset file pointer at offset {}: {}",
-                            current_offset, seek_err
-                        ),
-                    );
-                    last_error = Some(seek_err);
-                    break;
-                }
-                match file.read_exact(target_buf) {
-                    Ok(_) => {
-                        read_ok = true;
-                        break;
-                    }
-                    Err(e) => {
-                        last_error = Some(e);
-                    }
+            let mut results: Vec<Option<io::Result<(ScanStats, u64)>>> =
+                regions.iter().map(|_| None).collect();
+            loop {
+                match done_rx.recv_timeout(Duration::from_millis(100)) {
+                    Ok((index, result)) => results[index] = Some(result),
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
                 }
-            }
+                let verified = shared.verified.load(Ordering::Relaxed);
+                shared.watchdog.check();
 
-            if !read_ok {
-                if read_error_blocks < 5 {
-                    if let Some(e) = last_error.as_ref() {
-                        println!(
-                            "[ERROR] Read failed at offset {}: {}. Skipping block.",
-                            current_offset, e
-                        );
-                    }
+                if last_checkpoint_time.elapsed() >= CHECKPOINT_INTERVAL {
+                    save_progress();
+                    last_checkpoint_time = Instant::now();
                 }
-                read_error_blocks += 1;
-                consecutive_bad_blocks += 1;
-                if consecutive_bad_blocks >= MAX_CONSECUTIVE_BAD_BLOCKS {
-                    emit_error(
-                        sink,
-                        format!(
-                            "Aborting verify after {} consecutive bad blocks.",
-                            consecutive_bad_blocks
-                        ),
-                    );
-                    break;
+
+                if last_log_time.elapsed().as_secs() >= 2 {
+                    let percent = (verified as f64 / total_bytes as f64) * 100.0;
+                    let total_errors = shared.errors.load(Ordering::Relaxed);
+                    println!("[PROGRESS] {:.1}% (errors: {})", percent, total_errors);
+                    last_log_time = Instant::now();
                 }
-                if let Err(seek_err) =
-                    file.seek(SeekFrom::Start(current_offset + read_len as u64))
-                {
-                    emit_error(
+
+                if last_emit_time.elapsed().as_millis() >= 500 {
+                    emit_progress(
                         sink,
-                        format!(
-                            "Unable to seek past read failure at offset {}: {}",
-                            current_offset, seek_err
-                        ),
+                        super::ProgressUpdate {
+                            phase: super::ProgressPhase::Verify,
+                            percent: percent_of(verified, total_bytes),
+                            speed_mbps: speed_mbps(verified, start_time),
+                            bytes_written: total_bytes,
+                            bytes_verified: verified,
+                            total_bytes,
+                        },
                     );
-                    break;
+                    last_emit_time = Instant::now();
                 }
-                current_offset += read_len as u64;
-                continue;
             }
+            results
+        });
 
-            match core_logic::verify_block(current_offset, target_buf) {
-                Ok(_) => {
-                    valid_bytes += read_len as u64;
-                    consecutive_bad_blocks = 0;
+        let mut stats = ScanStats {
+            write_speed_mbps: written.speed_mbps,
+            write_latency: written.latency.clone(),
+            read_latency: shared.watchdog.read_latency(),
+            write_throughput: written.throughput.clone(),
+            write_cache_skip: written.cache_skip,
+            read_throughput: shared.throughput.lock().unwrap().curve(),
+            geometry: Some(geometry),
+            buffered_io: self.is_buffered(),
+            ..ScanStats::default()
+        };
+        let mut tested_bytes: u64 = 0;
+        let mut failure = None;
+        for result in results.into_iter().flatten() {
+            match result {
+                Ok((region_stats, region_bytes)) => {
+                    stats.merge(&region_stats);
+                    tested_bytes += region_bytes;
                 }
-                Err(bad_idx) => {
-                    let global_pos = current_offset + bad_idx as u64;
-                    if mismatch_blocks < 5 {
-                        println!(
-                            "[FAILURE] Mismatch at offset 0x{:X} ({}).",
-                            global_pos, global_pos
-                        );
-                    }
-                    mismatch_blocks += 1;
-                    consecutive_bad_blocks += 1;
-                    if consecutive_bad_blocks >= MAX_CONSECUTIVE_BAD_BLOCKS {
-                        emit_error(
-                            sink,
-                            format!(
-                                "Aborting verify after {} consecutive bad blocks.",
-                                consecutive_bad_blocks
-                            ),
-                        );
-                        break;
-                    }
-
-                    let mut expected = vec![0u8; read_len];
-                    core_logic::fill_block(current_offset, &mut expected);
-                    if let Some(status) = core_logic::analyze_failure_sample(&expected, target_buf)
-                    {
-                        let replace = match sample_status {
-                            None => true,
-                            Some(existing) => {
-                                let existing_severity = match existing {
-                                    DriveHealthStatus::Healthy => 0,
-                                    DriveHealthStatus::PhysicalCorruption => 1,
-                                    DriveHealthStatus::DataLoss => 2,
-                                    DriveHealthStatus::FakeCapacity => 3,
-                                };
-                                let new_severity = match status {
-                                    DriveHealthStatus::Healthy => 0,
-                                    DriveHealthStatus::PhysicalCorruption => 1,
-                                    DriveHealthStatus::DataLoss => 2,
-                                    DriveHealthStatus::FakeCapacity => 3,
-                                };
-                                new_severity > existing_severity
-                            }
-                        };
-                        if replace {
-                            sample_status = Some(status);
-                        }
-                    }
+                Err(e) => {
+                    failure.get_or_insert(e);
                 }
             }
+        }
 
-            current_offset += read_len as u64;
-
-            if last_log_time.elapsed().as_secs() >= 2 {
-                let percent = (current_offset as f64 / total_bytes as f64) * 100.0;
-                let total_errors = mismatch_blocks + read_error_blocks;
-                println!("[PROGRESS] {:.1}% (errors: {})", percent, total_errors);
-                last_log_time = Instant::now();
-            }
-
-            if last_emit_time.elapsed().as_millis() >= 500 {
-                emit_progress(
-                    sink,
-                    super::ProgressUpdate {
-                        phase: super::ProgressPhase::Verify,
-                        percent: percent_of(current_offset, total_bytes),
-                        speed_mbps: speed_mbps(current_offset, start_time),
-                        bytes_written: total_bytes,
-                        bytes_verified: current_offset,
-                        total_bytes,
-                    },
-                );
-                last_emit_time = Instant::now();
+        // Only a cancelled verify is picked up again. Any other end, including
+        // a stop at the bad block limit, is final and must not leave a
+        // checkpoint behind for a later resume.
+        if checkpointing {
+            let cancelled = should_cancel(&shared.cancel_flag);
+            if cancelled && failure.is_none() && tested_bytes < total_bytes {
+                save_progress();
+            } else {
+                self.remove_checkpoint();
             }
         }
+        if let Some(e) = failure {
+            return Err(e);
+        }
 
-        let report = core_logic::generate_report(
-            total_bytes,
-            current_offset,
-            valid_bytes,
-            mismatch_blocks,
-            read_error_blocks,
-            sample_status,
-        );
+        let verified_now = tested_bytes - already_verified;
+        if verified_now > 0 {
+            stats.verify_speed_mbps = Some(speed_mbps(verified_now, start_time));
+        }
+        let report = core_logic::generate_report(total_bytes, tested_bytes, &stats, self.pattern);
 
         println!(
             "[RESULT] Verify complete: status={:?}, errors={}.",
@@ -211,14 +375,331 @@ impl super::DriveInspector {
             sink,
             super::ProgressUpdate {
                 phase: super::ProgressPhase::Verify,
-                percent: percent_of(current_offset, total_bytes),
-                speed_mbps: speed_mbps(current_offset, start_time),
+                percent: percent_of(tested_bytes, total_bytes),
+                speed_mbps: speed_mbps(tested_bytes, start_time),
                 bytes_written: total_bytes,
-                bytes_verified: current_offset,
+                bytes_verified: tested_bytes,
                 total_bytes,
             },
         );
 
         Ok(report)
     }
+
+    // Checks the pieces of one region in order on the calling thread, each
+    // with its own handle, and returns the stats along with how many bytes it
+    // got through. Both include what the region had covered before a resume.
+    fn verify_region(
+        &self,
+        index: usize,
+        region: RegionProgress,
+        pieces: Vec<RegionPiece>,
+        shared: &VerifyShared,
+    ) -> io::Result<(ScanStats, u64)> {
+        let block_size = shared.block_size;
+        let mut reread_buffer = AlignedBuffer::new(block_size, shared.alignment)?;
+        let mut stats = region.stats;
+        let mut last_publish_time = Instant::now();
+        let publish = |position: u64, stats: &ScanStats| {
+            if let Some(progress) = &shared.progress {
+                progress.lock().unwrap()[index] = RegionProgress {
+                    start: region.start,
+                    position,
+                    end: region.end,
+                    stats: stats.clone(),
+                };
+            }
+        };
+        let mut consecutive_bad_blocks: u64 = 0;
+        let mut alias_searches: u32 = 0;
+        let mut rereads: u32 = 0;
+        let mut tested_bytes: u64 = region.position - region.start;
+        // Bytes the region had covered before a resume, walked off piece by
+        // piece. Each piece has its own block order, so what it completed is a
+        // whole number of blocks from the start of that order.
+        let mut skip_bytes = tested_bytes;
+
+        for RegionPiece {
+            mut file,
+            base,
+            range,
+        } in pieces
+        {
+            if shared.should_stop() {
+                break;
+            }
+            let piece_len = range.end - range.start;
+            if skip_bytes >= piece_len {
+                skip_bytes -= piece_len;
+                continue;
+            }
+            let blocks = BlockOrder::new(
+                self.verify_order,
+                (range.start - base)..(range.end - base),
+                block_size as u64,
+                self.pattern.session_seed ^ VERIFY_ORDER_SALT,
+            )
+            .skip_blocks(skip_bytes.div_ceil(block_size as u64));
+            skip_bytes = 0;
+            let queue = BlockQueue::new(self.io_engine, file.try_clone()?, self.queue_depth)?;
+            let buffers = (0..queue.depth() + READ_AHEAD_BLOCKS)
+                .map(|_| AlignedBuffer::new(block_size, shared.alignment))
+                .collect::<io::Result<Vec<_>>>()?;
+
+            thread::scope(|scope| -> io::Result<()> {
+                let mut reads = ReadAhead::spawn(scope, queue, buffers, blocks);
+                while !shared.should_stop() {
+                    let Some(done) = reads.next()? else {
+                        break;
+                    };
+                    let current_offset = base + done.offset;
+                    shared.watchdog.record_read(current_offset, done.latency);
+                    let mut read_ok = done.is_complete();
+                    let read_len = done.len;
+                    let mut last_error = match done.result {
+                        Ok(count) if count < read_len => {
+                            Some(io::Error::from(ErrorKind::UnexpectedEof))
+                        }
+                        Ok(_) => None,
+                        Err(e) => Some(e),
+                    };
+                    let mut buffer = done.buffer;
+                    let target_buf = &mut buffer.as_mut_slice()[0..read_len];
+
+                    for _ in 0..MAX_READ_RETRIES {
+                        if read_ok {
+                            break;
+                        }
+                        if let Err(seek_err) = file.seek(SeekFrom::Start(current_offset - base)) {
+                            emit_error(
+                                shared.sink,
+                                format!(
+                                    "Unable to reset file pointer at offset {}: {}",
+                                    current_offset, seek_err
+                                ),
+                            );
+                            last_error = Some(seek_err);
+                            break;
+                        }
+                        match file.read_exact(target_buf) {
+                            Ok(_) => {
+                                // A read that recovers returned the same data
+                                // it would have; only a differing re-read
+                                // makes a block unstable.
+                                read_ok = true;
+                                stats.recovered_reads += 1;
+                            }
+                            Err(e) => {
+                                last_error = Some(e);
+                            }
+                        }
+                    }
+
+                    if !read_ok {
+                        if stats.read_error_blocks < 5
+                            && let Some(e) = last_error.as_ref()
+                        {
+                            println!(
+                                "[ERROR] Read failed at offset {}: {}. Skipping block.",
+                                current_offset, e
+                            );
+                        }
+                        stats.record_read_error(current_offset, read_len as u64);
+                        shared.errors.fetch_add(1, Ordering::Relaxed);
+                        consecutive_bad_blocks += 1;
+                        let disconnected = last_error
+                            .as_ref()
+                            .and_then(device_error_status)
+                            .is_some_and(|status| status == DriveHealthStatus::Disconnected)
+                            || !Path::new(&self.file_path).exists();
+                        if disconnected {
+                            stats.record_status(DriveHealthStatus::Disconnected);
+                            emit_error(
+                                shared.sink,
+                                format!("Device disconnected at offset {}.", current_offset),
+                            );
+                            shared.stop.store(true, Ordering::Relaxed);
+                            break;
+                        }
+                        if consecutive_bad_blocks >= MAX_CONSECUTIVE_BAD_BLOCKS {
+                            emit_error(
+                                shared.sink,
+                                format!(
+                                    "Aborting verify after {} consecutive bad blocks.",
+                                    consecutive_bad_blocks
+                                ),
+                            );
+                            shared.stop.store(true, Ordering::Relaxed);
+                            break;
+                        }
+                        reads.recycle(buffer);
+                        tested_bytes += read_len as u64;
+                        shared.count_verified(read_len as u64);
+                        continue;
+                    }
+
+                    match core_logic::verify_block(self.pattern, current_offset, target_buf) {
+                        Ok(_) => {
+                            stats.record_valid(current_offset, read_len as u64);
+                            consecutive_bad_blocks = 0;
+                        }
+                        Err(bad_idx) => {
+                            let global_pos = current_offset + bad_idx as u64;
+                            let extents = core_logic::mismatch_extents(
+                                self.pattern,
+                                current_offset,
+                                target_buf,
+                            );
+                            if stats.mismatch_blocks < 5 {
+                                let bad_bytes: usize =
+                                    extents.iter().map(|extent| extent.len()).sum();
+                                println!(
+                                    "[FAILURE] Mismatch at offset 0x{:X} ({}): {} bytes in {} ranges.",
+                                    global_pos,
+                                    global_pos,
+                                    bad_bytes,
+                                    extents.len()
+                                );
+                            }
+                            stats.record_mismatch(current_offset, read_len as u64, &extents);
+                            shared.errors.fetch_add(1, Ordering::Relaxed);
+                            consecutive_bad_blocks += 1;
+                            if consecutive_bad_blocks >= MAX_CONSECUTIVE_BAD_BLOCKS {
+                                emit_error(
+                                    shared.sink,
+                                    format!(
+                                        "Aborting verify after {} consecutive bad blocks.",
+                                        consecutive_bad_blocks
+                                    ),
+                                );
+                                shared.stop.store(true, Ordering::Relaxed);
+                                break;
+                            }
+
+                            if rereads < MAX_REREADS {
+                                rereads += 1;
+                                let reread = &mut reread_buffer.as_mut_slice()[0..read_len];
+                                let reread_ok = file
+                                    .seek(SeekFrom::Start(current_offset - base))
+                                    .and_then(|_| file.read_exact(reread))
+                                    .is_ok();
+                                if reread_ok && reread != &*target_buf {
+                                    if stats.unstable_blocks == 0 {
+                                        println!(
+                                            "[FAILURE] Offset {} returned different data on re-read.",
+                                            current_offset
+                                        );
+                                    }
+                                    stats.unstable_blocks += 1;
+                                    stats.record_status(DriveHealthStatus::Unstable);
+                                }
+                            }
+
+                            let mut expected = vec![0u8; read_len];
+                            core_logic::fill_block(self.pattern, current_offset, &mut expected);
+                            let mut misplaced = core_logic::find_misplaced_sector(
+                                self.pattern.session_seed,
+                                current_offset,
+                                target_buf,
+                            );
+                            if misplaced.is_none()
+                                && stats.can_sample_misplaced()
+                                && alias_searches < MAX_ALIAS_SEARCHES
+                            {
+                                alias_searches += 1;
+                                let candidates = core_logic::alias_candidates(
+                                    current_offset,
+                                    read_len as u64,
+                                    block_size as u64,
+                                    shared.total_bytes,
+                                );
+                                misplaced = core_logic::find_matching_offset(
+                                    self.pattern,
+                                    &candidates,
+                                    current_offset,
+                                    target_buf,
+                                )
+                                .map(|written_offset| MisplacedSector {
+                                    read_offset: current_offset,
+                                    written_offset,
+                                });
+                            }
+                            if let Some(sector) = misplaced {
+                                if stats.misplaced_sectors.is_empty() {
+                                    println!(
+                                        "[FAILURE] Offset {} returned the data written at offset {}.",
+                                        sector.read_offset, sector.written_offset
+                                    );
+                                }
+                                stats.record_misplaced(sector);
+                                if stats.can_sample_misplaced() {
+                                    let reread = &mut reread_buffer.as_mut_slice()[0..read_len];
+                                    let nearer = core_logic::nearer_alias(
+                                        sector,
+                                        block_size as u64,
+                                        shared.total_bytes,
+                                        |modulus| {
+                                            let offset = current_offset + modulus;
+                                            offset + read_len as u64 <= shared.total_bytes
+                                                && file
+                                                    .seek(SeekFrom::Start(offset - base))
+                                                    .and_then(|_| file.read_exact(reread))
+                                                    .is_ok()
+                                                && *reread == *target_buf
+                                        },
+                                    );
+                                    if let Some(nearer) = nearer {
+                                        stats.record_misplaced(nearer);
+                                    }
+                                }
+                            }
+                            let kind = core_logic::classify_failure(
+                                self.pattern,
+                                current_offset,
+                                &expected,
+                                target_buf,
+                                misplaced.is_some(),
+                                &mut stats.fingerprints,
+                            );
+                            if let Some(kind) = kind {
+                                if kind == FailureKind::BitCorruption {
+                                    stats
+                                        .bit_errors
+                                        .record(current_offset, &expected, target_buf);
+                                }
+                                stats.record_failure(kind);
+                            }
+                        }
+                    }
+
+                    reads.recycle(buffer);
+                    tested_bytes += read_len as u64;
+                    shared.count_verified(read_len as u64);
+                    if last_publish_time.elapsed() >= PROGRESS_PUBLISH_INTERVAL {
+                        publish(region.start + tested_bytes, &stats);
+                        last_publish_time = Instant::now();
+                    }
+                }
+                Ok(())
+            })?;
+        }
+
+        publish(region.start + tested_bytes, &stats);
+        Ok((stats, tested_bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_regions;
+
+    #[test]
+    fn test_regions_cover_range_on_block_boundaries() {
+        assert_eq!(
+            split_regions(10 * 4096, 4096, 3),
+            vec![0..16384, 16384..32768, 32768..40960]
+        );
+        assert_eq!(split_regions(4096, 4096, 4), vec![0..4096]);
+        assert_eq!(split_regions(8192, 4096, 1), vec![0..8192]);
+    }
 }
@@ -0,0 +1,146 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::thread::Scope;
+use std::time::{Duration, Instant};
+
+use crate::core_logic::LatencyStats;
+
+use super::progress::{EventSink, ProgressPhase, StallEvent, emit_stall};
+
+const WATCH_INTERVAL: Duration = Duration::from_millis(100);
+
+// Times every request of one phase and watches for the device going quiet. A
+// stall is announced while it is still going on, once per quiet spell, and
+// again with its offset when the slow request finally returns. A zero
+// threshold keeps the timings but never reports a stall.
+pub(super) struct IoWatchdog<'a> {
+    phase: ProgressPhase,
+    threshold: Duration,
+    sink: Option<&'a dyn EventSink>,
+    start: Instant,
+    last_completion_us: AtomicU64,
+    reported_us: AtomicU64,
+    writes: Mutex<LatencyStats>,
+    reads: Mutex<LatencyStats>,
+    stopped: Mutex<bool>,
+    wake: Condvar,
+}
+
+// Stops the watching thread when dropped, so every early return out of the
+// phase also lets its scope finish.
+pub(super) struct Watching<'w, 'a> {
+    watchdog: &'w IoWatchdog<'a>,
+}
+
+impl Drop for Watching<'_, '_> {
+    fn drop(&mut self) {
+        *self.watchdog.stopped.lock().unwrap() = true;
+        self.watchdog.wake.notify_all();
+    }
+}
+
+impl<'a> IoWatchdog<'a> {
+    pub(super) fn new(
+        phase: ProgressPhase,
+        threshold: Duration,
+        sink: Option<&'a dyn EventSink>,
+    ) -> Self {
+        Self {
+            phase,
+            threshold,
+            sink,
+            start: Instant::now(),
+            last_completion_us: AtomicU64::new(0),
+            reported_us: AtomicU64::new(u64::MAX),
+            writes: Mutex::new(LatencyStats::with_stall_threshold(threshold)),
+            reads: Mutex::new(LatencyStats::with_stall_threshold(threshold)),
+            stopped: Mutex::new(false),
+            wake: Condvar::new(),
+        }
+    }
+
+    pub(super) fn record_write(&self, offset: u64, latency: Duration) {
+        self.record(&self.writes, "Write", offset, latency);
+    }
+
+    pub(super) fn record_read(&self, offset: u64, latency: Duration) {
+        self.record(&self.reads, "Read", offset, latency);
+    }
+
+    pub(super) fn write_latency(&self) -> LatencyStats {
+        self.writes.lock().unwrap().clone()
+    }
+
+    pub(super) fn read_latency(&self) -> LatencyStats {
+        self.reads.lock().unwrap().clone()
+    }
+
+    // Checks on a thread of its own, for phases that block in I/O on the
+    // thread that would otherwise call `check`.
+    pub(super) fn watch<'w>(&'w self, scope: &'w Scope<'w, '_>) -> Watching<'w, 'a> {
+        if !self.threshold.is_zero() {
+            scope.spawn(move || {
+                let mut stopped = self.stopped.lock().unwrap();
+                while !*stopped {
+                    stopped = self.wake.wait_timeout(stopped, WATCH_INTERVAL).unwrap().0;
+                    self.check();
+                }
+            });
+        }
+        Watching { watchdog: self }
+    }
+
+    pub(super) fn check(&self) {
+        if self.threshold.is_zero() {
+            return;
+        }
+        let last = self.last_completion_us.load(Ordering::Relaxed);
+        let quiet = Duration::from_micros(self.elapsed_us().saturating_sub(last));
+        if quiet > self.threshold && self.reported_us.swap(last, Ordering::Relaxed) != last {
+            println!(
+                "[WARN] Stall: no I/O has completed for {} ms.",
+                quiet.as_millis()
+            );
+            emit_stall(
+                self.sink,
+                StallEvent {
+                    phase: self.phase,
+                    offset: None,
+                    duration_ms: quiet.as_millis() as u64,
+                    finished: false,
+                },
+            );
+        }
+    }
+
+    fn record(&self, stats: &Mutex<LatencyStats>, kind: &str, offset: u64, latency: Duration) {
+        self.last_completion_us
+            .store(self.elapsed_us(), Ordering::Relaxed);
+        let stalled = {
+            let mut stats = stats.lock().unwrap();
+            stats.record(offset, latency);
+            stats.is_stall(latency)
+        };
+        if stalled {
+            println!(
+                "[WARN] {} at offset {} stalled for {} ms.",
+                kind,
+                offset,
+                latency.as_millis()
+            );
+            emit_stall(
+                self.sink,
+                StallEvent {
+                    phase: self.phase,
+                    offset: Some(offset),
+                    duration_ms: latency.as_millis() as u64,
+                    finished: true,
+                },
+            );
+        }
+    }
+
+    fn elapsed_us(&self) -> u64 {
+        self.start.elapsed().as_micros() as u64
+    }
+}
@@ -1,17 +1,42 @@
-use std::io::{self, ErrorKind, Write};
+use std::fs::{self, File};
+use std::io::{self, ErrorKind};
 use std::path::Path;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
+use std::thread;
 use std::time::Instant;
 
-use crate::core_logic;
+use crate::core_logic::{CacheEstimateSkip, LatencyStats, ThroughputCurve};
 
-use super::DIRECT_IO_ALIGNMENT;
-use super::direct_io::{AlignedBuffer, align_down_u64, open_direct_write, resolve_block_size};
+use super::checkpoint::{CHECKPOINT_INTERVAL, Checkpoint, CheckpointPhase};
+use super::direct_io::{AlignedBuffer, align_down_u64, flush_and_evict, resolve_block_size};
+use super::order::{AccessOrder, BlockOrder};
+use super::pipeline::FillPipeline;
 use super::progress::{emit_error, emit_progress, percent_of, should_cancel, speed_mbps};
+use super::queue::{BlockQueue, is_storage_full};
+use super::segments::{discover_segments, remove_segments, segment_path};
+use super::throughput::ThroughputRecorder;
+use super::watchdog::IoWatchdog;
+
+// Gives the write order its own sequence, apart from the verify order.
+const WRITE_ORDER_SALT: u64 = 0x5752_4954_454f_5244;
+
+// What the write phase hands on to verify: how far it got and how the device
+// behaved while taking the data. A verify of data written earlier only has
+// `bytes_written`. Only a verify that follows a write in the same run keeps a
+// checkpoint, since only that run can be resumed.
+#[derive(Debug, Clone, Default)]
+pub struct WriteSummary {
+    pub bytes_written: u64,
+    pub speed_mbps: Option<f64>,
+    pub latency: LatencyStats,
+    pub throughput: ThroughputCurve,
+    pub cache_skip: Option<CacheEstimateSkip>,
+    pub resumable: bool,
+}
 
 impl super::DriveInspector {
-    pub fn run_write_phase(&self, limit_mb: u64) -> io::Result<u64> {
+    pub fn run_write_phase(&self, limit_mb: u64) -> io::Result<WriteSummary> {
         self.run_write_phase_with_events(limit_mb, None, None)
     }
 
@@ -20,126 +45,187 @@ impl super::DriveInspector {
         limit_mb: u64,
         cancel_flag: Option<Arc<AtomicBool>>,
         sink: Option<&dyn super::EventSink>,
-    ) -> io::Result<u64> {
+    ) -> io::Result<WriteSummary> {
+        self.write_phase(limit_mb, None, cancel_flag, sink)
+    }
+
+    pub fn resume_write_phase(&self, checkpoint: &Checkpoint) -> io::Result<WriteSummary> {
+        self.resume_write_phase_with_events(checkpoint, None, None)
+    }
+
+    // Carries on writing after the prefix a write-phase checkpoint recorded.
+    // The inspector must have been built `with_checkpoint`.
+    pub fn resume_write_phase_with_events(
+        &self,
+        checkpoint: &Checkpoint,
+        cancel_flag: Option<Arc<AtomicBool>>,
+        sink: Option<&dyn super::EventSink>,
+    ) -> io::Result<WriteSummary> {
+        self.write_phase(0, Some(checkpoint), cancel_flag, sink)
+    }
+
+    fn write_phase(
+        &self,
+        limit_mb: u64,
+        resume: Option<&Checkpoint>,
+        cancel_flag: Option<Arc<AtomicBool>>,
+        sink: Option<&dyn super::EventSink>,
+    ) -> io::Result<WriteSummary> {
         let path = Path::new(&self.file_path);
+        let resumed_bytes = resume.map_or(0, |checkpoint| checkpoint.bytes_written());
 
-        if let Some(parent) = path.parent() {
-            if !parent.exists() {
+        // Resuming reopens the file the checkpoint stopped in, keeping what it
+        // already holds; a fresh run starts the first file from scratch.
+        let (mut index, mut base, first_path) = if self.multi_file {
+            if !path.is_dir() {
+                return Err(io::Error::new(
+                    ErrorKind::NotFound,
+                    "Target directory does not exist.",
+                ));
+            }
+            if resumed_bytes > 0 {
+                let segments = discover_segments(path, resumed_bytes)?;
+                let found: u64 = segments.iter().map(|segment| segment.len).sum();
+                if found < resumed_bytes {
+                    return Err(io::Error::new(
+                        ErrorKind::NotFound,
+                        format!("Test file series ends after {found} of {resumed_bytes} bytes."),
+                    ));
+                }
+                let index = segments.len() - 1;
+                (index, segments[index].start, segment_path(path, index))
+            } else {
+                remove_segments(path)?;
+                (0, 0, segment_path(path, 0))
+            }
+        } else {
+            if let Some(parent) = path.parent()
+                && !parent.exists()
+            {
                 return Err(io::Error::new(
                     ErrorKind::NotFound,
                     "Parent directory does not exist.",
                 ));
             }
-        }
+            (0, 0, path.to_path_buf())
+        };
 
-        let block_size = resolve_block_size(self.block_size)?;
-        let mut file = open_direct_write(path).map_err(|e| {
+        let mut file = if resume.is_some() {
+            self.open_target_write_with(&first_path, false)
+        } else {
+            self.forget_previous_run()?;
+            self.open_target_write(&first_path)
+        }
+        .map_err(|e| {
             emit_error(sink, format!("Unable to open target for writing: {}", e));
             e
         })?;
+        let alignment = self.detect_geometry(&file).alignment as usize;
+        let block_size = resolve_block_size(self.block_size, alignment)?;
 
-        let mut buffer = AlignedBuffer::new(block_size, DIRECT_IO_ALIGNMENT)?;
-        let mut current_offset: u64 = 0;
-        let limit_bytes_raw = if limit_mb == 0 {
-            u64::MAX
-        } else {
-            limit_mb * 1024 * 1024
+        let limit_bytes_raw = match resume {
+            Some(checkpoint) => checkpoint.limit_bytes,
+            None => self.limit_bytes(limit_mb)?,
         };
-        let limit_bytes = align_down_u64(limit_bytes_raw, DIRECT_IO_ALIGNMENT as u64);
-        if limit_mb > 0 && limit_bytes == 0 {
+        let bounded = limit_bytes_raw != u64::MAX;
+        let limit_bytes = align_down_u64(limit_bytes_raw, alignment as u64);
+        // Out of order, the blocks that fit before the volume fills are not a
+        // prefix, so the size has to be known up front.
+        if !bounded && self.write_order != AccessOrder::Sequential {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "{} write order needs a size limit.",
+                    self.write_order.name()
+                ),
+            ));
+        }
+        if bounded && limit_bytes == 0 {
             return Err(io::Error::new(
                 ErrorKind::InvalidInput,
                 "Limit too small for direct I/O alignment.",
             ));
         }
-        if limit_mb > 0 && limit_bytes != limit_bytes_raw {
+        if bounded && limit_bytes != limit_bytes_raw {
             println!(
                 "[INFO] Limit aligned from {} to {} bytes for direct I/O.",
                 limit_bytes_raw, limit_bytes
             );
         }
-        let total_bytes = if limit_mb == 0 { 0 } else { limit_bytes };
-        let start_time = Instant::now();
-        let mut last_log_time = Instant::now();
-        let mut last_emit_time = Instant::now();
+        if !resumed_bytes.is_multiple_of(alignment as u64) {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "Checkpoint offset is not aligned for direct I/O.",
+            ));
+        }
+        let mut run = WriteRun {
+            block_size,
+            alignment,
+            limit_bytes: limit_bytes_raw,
+            total_bytes: if bounded { limit_bytes } else { 0 },
+            written: resumed_bytes,
+            resumed_bytes,
+            start_time: Instant::now(),
+            last_log_time: Instant::now(),
+            last_emit_time: Instant::now(),
+            last_checkpoint_time: Instant::now(),
+            cancel_flag,
+            sink,
+            throughput: ThroughputRecorder::new(self.curve_region_bytes, resumed_bytes),
+        };
+        if resumed_bytes > 0 {
+            println!("[INFO] Resuming write at offset {}.", resumed_bytes);
+        }
 
         println!(
-            "[INFO] Write phase start. Target={}, Limit={}MB",
-            self.file_path, limit_mb
+            "[INFO] Write phase start. Target={}, Limit={}MB, Session={:016x}",
+            self.file_path,
+            run.total_bytes / 1024 / 1024,
+            self.pattern.session_seed
         );
 
-        let mut stop_due_to_full = false;
-        while current_offset < limit_bytes && !should_cancel(&cancel_flag) {
-            let remaining = limit_bytes.saturating_sub(current_offset);
-            if remaining == 0 {
-                break;
-            }
-
-            let write_len = std::cmp::min(remaining, block_size as u64) as usize;
-            let target_buf = &mut buffer.as_mut_slice()[0..write_len];
-            core_logic::fill_block(current_offset, target_buf);
-
-            let mut written = 0;
-            while written < write_len {
-                match file.write(&target_buf[written..]) {
-                    Ok(0) => {
-                        println!("[INFO] Write stopped: storage full.");
-                        stop_due_to_full = true;
+        let watchdog = IoWatchdog::new(super::ProgressPhase::Write, self.stall_threshold, sink);
+        thread::scope(|scope| -> io::Result<()> {
+            let _watching = watchdog.watch(scope);
+            if !self.multi_file {
+                self.write_file(&file, 0, limit_bytes, &mut run, &watchdog)?;
+                sync_file(&file, sink)?;
+            } else {
+                // A file ends when it reaches the segment size or the filesystem
+                // refuses to grow it (ENOSPC, EFBIG). Only a new file that takes
+                // no data at all means the volume is full.
+                let segment_bytes =
+                    align_down_u64(self.segment_bytes, alignment as u64).max(alignment as u64);
+                loop {
+                    let end = limit_bytes.min(base.saturating_add(segment_bytes));
+                    self.write_file(&file, base, end, &mut run, &watchdog)?;
+                    sync_file(&file, sink)?;
+                    drop(file);
+                    if run.written == base {
+                        fs::remove_file(segment_path(path, index))?;
                         break;
                     }
-                    Ok(count) => {
-                        written += count;
-                        current_offset += count as u64;
-                    }
-                    Err(e) => {
-                        if e.kind() == ErrorKind::WriteZero || e.kind() == ErrorKind::StorageFull {
-                            println!("[INFO] Write stopped: storage full.");
-                            stop_due_to_full = true;
-                            break;
-                        }
-                        emit_error(
-                            sink,
-                            format!("Write failure at offset {}: {}", current_offset, e),
-                        );
-                        return Err(e);
+                    if run.written >= limit_bytes || should_cancel(&run.cancel_flag) {
+                        break;
                     }
+                    index += 1;
+                    base = run.written;
+                    let next_path = segment_path(path, index);
+                    println!("[INFO] Continuing in {}.", next_path.display());
+                    file = self.open_target_write(&next_path).map_err(|e| {
+                        emit_error(sink, format!("Unable to open target for writing: {}", e));
+                        e
+                    })?;
                 }
             }
+            Ok(())
+        })?;
 
-            if stop_due_to_full {
-                break;
-            }
-
-            if last_log_time.elapsed().as_secs() >= 2 {
-                let mb_written = current_offset / 1024 / 1024;
-                println!("[PROGRESS] Written {} MB", mb_written);
-                last_log_time = Instant::now();
-            }
-
-            if last_emit_time.elapsed().as_millis() >= 500 {
-                emit_progress(
-                    sink,
-                    super::ProgressUpdate {
-                        phase: super::ProgressPhase::Write,
-                        percent: percent_of(current_offset, total_bytes),
-                        speed_mbps: speed_mbps(current_offset, start_time),
-                        bytes_written: current_offset,
-                        bytes_verified: 0,
-                        total_bytes,
-                    },
-                );
-                last_emit_time = Instant::now();
-            }
-        }
-
-        println!("[INFO] Syncing data...");
-        if let Err(e) = file.sync_all() {
-            emit_error(sink, format!("Failed to sync data: {}", e));
-            return Err(e);
-        }
-
+        let current_offset = run.written;
+        let start_time = run.start_time;
+        let total_bytes = run.total_bytes;
         let duration = start_time.elapsed();
-        let mb_total = current_offset / 1024 / 1024;
+        let mb_total = (current_offset - resumed_bytes) / 1024 / 1024;
         let speed = if duration.as_secs_f64() > 0.0 {
             mb_total as f64 / duration.as_secs_f64()
         } else {
@@ -153,18 +239,240 @@ impl super::DriveInspector {
             speed
         );
 
+        let average_speed = run.speed_mbps();
+        let cancelled = should_cancel(&run.cancel_flag);
+        if current_offset > 0 {
+            // A cancelled out-of-order write holds scattered blocks rather than
+            // a prefix, which only its checkpoint can describe.
+            if !cancelled || self.write_order == AccessOrder::Sequential {
+                self.save_manifest(current_offset, run.block_size);
+            }
+            // A cancelled write resumes as a write; a finished one goes
+            // straight on to verify.
+            let phase = if cancelled {
+                CheckpointPhase::Write
+            } else {
+                CheckpointPhase::Verify
+            };
+            self.save_checkpoint(&self.new_checkpoint(
+                phase,
+                run.limit_bytes,
+                current_offset,
+                average_speed,
+            ));
+        } else {
+            self.remove_checkpoint();
+        }
         emit_progress(
             sink,
             super::ProgressUpdate {
                 phase: super::ProgressPhase::Write,
                 percent: percent_of(current_offset, total_bytes),
-                speed_mbps: speed_mbps(current_offset, start_time),
+                speed_mbps: average_speed,
                 bytes_written: current_offset,
                 bytes_verified: 0,
                 total_bytes,
             },
         );
 
-        Ok(current_offset)
+        Ok(WriteSummary {
+            bytes_written: current_offset,
+            speed_mbps: (average_speed > 0.0).then_some(average_speed),
+            latency: watchdog.write_latency(),
+            throughput: run.throughput.curve(),
+            cache_skip: if resumed_bytes > 0 {
+                Some(CacheEstimateSkip::ResumedWrite)
+            } else if self.write_order != AccessOrder::Sequential {
+                Some(CacheEstimateSkip::NonSequentialWrite)
+            } else {
+                None
+            },
+            resumable: true,
+        })
+    }
+
+    // Writes the pattern for global offsets `base..end` into `file`, which
+    // starts at `base`. Stops early, without an error, when the file cannot
+    // grow any further or the run is cancelled.
+    fn write_file(
+        &self,
+        file: &File,
+        base: u64,
+        end: u64,
+        run: &mut WriteRun,
+        watchdog: &IoWatchdog,
+    ) -> io::Result<()> {
+        let block_size = run.block_size;
+        let mut queue = BlockQueue::new(self.io_engine, file.try_clone()?, self.queue_depth)?;
+        let mut free = (0..queue.depth() + self.worker_threads)
+            .map(|_| AlignedBuffer::new(block_size, run.alignment))
+            .collect::<io::Result<Vec<_>>>()?;
+        let mut blocks = BlockOrder::new(
+            self.write_order,
+            base..end,
+            block_size as u64,
+            self.pattern.session_seed ^ WRITE_ORDER_SALT,
+        )
+        .skip_blocks((run.written - base).div_ceil(block_size as u64))
+        .peekable();
+        let mut stop_due_to_full = false;
+        thread::scope(|scope| -> io::Result<()> {
+            let mut fills = FillPipeline::spawn(scope, self.pattern, self.worker_threads);
+            loop {
+                let stopping = stop_due_to_full || should_cancel(&run.cancel_flag);
+                while !stopping
+                    && let Some(&(offset, write_len)) = blocks.peek()
+                    && let Some(buffer) = free.pop()
+                {
+                    blocks.next();
+                    fills.submit(offset, write_len, buffer)?;
+                }
+
+                // Blocks already handed to the workers are discarded rather
+                // than written once the phase is stopping.
+                if fills.pending() > 0 && (stopping || queue.in_flight() < queue.depth()) {
+                    if let Some(filled) = fills.next()? {
+                        if stopping {
+                            free.push(filled.buffer);
+                        } else {
+                            queue.push_write(filled.offset - base, filled.buffer, filled.len)?;
+                        }
+                    }
+                    continue;
+                }
+
+                let Some(done) = queue.pop()? else {
+                    break;
+                };
+                watchdog.record_write(base + done.offset, done.latency);
+                let mut count = 0;
+                let full = match &done.result {
+                    Ok(written) => *written < done.len,
+                    Err(e) => is_storage_full(e),
+                };
+                if full && self.write_order != AccessOrder::Sequential {
+                    let message = format!(
+                        "Storage full at offset {} before the {} write finished.",
+                        base + done.offset,
+                        self.write_order.name()
+                    );
+                    emit_error(run.sink, message.clone());
+                    return Err(io::Error::new(ErrorKind::StorageFull, message));
+                }
+                match done.result {
+                    Ok(written) if !stop_due_to_full => {
+                        count = written as u64;
+                        if written < done.len {
+                            println!("[INFO] Write stopped: storage full.");
+                            stop_due_to_full = true;
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) if is_storage_full(&e) => {
+                        if !stop_due_to_full {
+                            println!(
+                                "[INFO] Write stopped at offset {}: {}.",
+                                base + done.offset,
+                                e
+                            );
+                            stop_due_to_full = true;
+                        }
+                    }
+                    Err(e) => {
+                        emit_error(
+                            run.sink,
+                            format!("Write failure at offset {}: {}", base + done.offset, e),
+                        );
+                        return Err(e);
+                    }
+                }
+                if self.is_buffered()
+                    && let Err(e) = flush_and_evict(file, done.offset, done.len as u64)
+                {
+                    emit_error(
+                        run.sink,
+                        format!(
+                            "Failed to flush data at offset {}: {}",
+                            base + done.offset,
+                            e
+                        ),
+                    );
+                    return Err(e);
+                }
+                free.push(done.buffer);
+                run.record(count);
+                if !stop_due_to_full && run.last_checkpoint_time.elapsed() >= CHECKPOINT_INTERVAL {
+                    // Only what has reached the media may be counted as done.
+                    file.sync_data()?;
+                    let checkpoint = self.new_checkpoint(
+                        CheckpointPhase::Write,
+                        run.limit_bytes,
+                        run.written,
+                        run.speed_mbps(),
+                    );
+                    self.save_checkpoint(&checkpoint);
+                    run.last_checkpoint_time = Instant::now();
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+// Running totals for one write phase, carried across every file it writes.
+struct WriteRun<'a> {
+    block_size: usize,
+    alignment: usize,
+    limit_bytes: u64,
+    total_bytes: u64,
+    written: u64,
+    resumed_bytes: u64,
+    start_time: Instant,
+    last_log_time: Instant,
+    last_emit_time: Instant,
+    last_checkpoint_time: Instant,
+    cancel_flag: Option<Arc<AtomicBool>>,
+    sink: Option<&'a dyn super::EventSink>,
+    throughput: ThroughputRecorder,
+}
+
+impl WriteRun<'_> {
+    // Only what this session wrote; a resumed run starts the clock again.
+    fn speed_mbps(&self) -> f64 {
+        speed_mbps(self.written - self.resumed_bytes, self.start_time)
     }
+
+    fn record(&mut self, count: u64) {
+        self.written += count;
+        self.throughput.record(count);
+
+        if self.last_log_time.elapsed().as_secs() >= 2 {
+            let mb_written = self.written / 1024 / 1024;
+            println!("[PROGRESS] Written {} MB", mb_written);
+            self.last_log_time = Instant::now();
+        }
+
+        if self.last_emit_time.elapsed().as_millis() >= 500 {
+            emit_progress(
+                self.sink,
+                super::ProgressUpdate {
+                    phase: super::ProgressPhase::Write,
+                    percent: percent_of(self.written, self.total_bytes),
+                    speed_mbps: self.speed_mbps(),
+                    bytes_written: self.written,
+                    bytes_verified: 0,
+                    total_bytes: self.total_bytes,
+                },
+            );
+            self.last_emit_time = Instant::now();
+        }
+    }
+}
+
+fn sync_file(file: &File, sink: Option<&dyn super::EventSink>) -> io::Result<()> {
+    println!("[INFO] Syncing data...");
+    file.sync_all().map_err(|e| {
+        emit_error(sink, format!("Failed to sync data: {}", e));
+        e
+    })
 }
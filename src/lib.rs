@@ -4,7 +4,22 @@ mod core_logic;
 mod i18n;
 mod io_controller;
 
-pub use crate::app::{RunError, RunOutcome, run_cli, run_write_verify};
+pub use crate::app::{
+    RunError, RunOutcome, resume_run, run_cli, run_in_place, run_passes, run_verify_only,
+    run_write_verify,
+};
 pub use crate::config::AppConfig;
-pub use crate::core_logic::{DiagnosisReport, DriveHealthStatus};
-pub use crate::io_controller::{DriveInspector, EventSink, ProgressPhase, ProgressUpdate};
+pub use crate::core_logic::{
+    ALTERNATING, ALTERNATING_INVERTED, BUILTIN_GENERATORS, BadRange, BadRangeKind, BitErrorStats,
+    BitPositionErrors, BlockStream, CHACHA20, CHACHA20_INVERTED, CacheEstimateSkip,
+    ChaCha20Generator, DiagnosisReport, DriveGeometry, DriveHealthStatus, FailureBreakdown,
+    FailureKind, FixedGenerator, InvertedGenerator, LatencyStats, MisplacedSector, ONES,
+    PassReport, Pattern, PatternGenerator, PatternLayout, PatternStream, SPLITMIX,
+    SPLITMIX_INVERTED, SlowIo, SplitMixGenerator, StuckBit, ThroughputCurve, ThroughputPoint,
+    WYRAND, WYRAND_INVERTED, WriteCacheEstimate, WyRandGenerator, ZEROS, badblocks_list,
+    builtin_generator, estimate_write_cache, inverse_generator,
+};
+pub use crate::io_controller::{
+    AccessOrder, Checkpoint, CheckpointPhase, DriveInspector, EventSink, IoEngine, Manifest,
+    ProgressPhase, ProgressUpdate, StallEvent, WriteSummary, device_error_status,
+};